@@ -23,7 +23,9 @@ function js_lvl_to_rust_lvl(js_level) {
       field_size: attractor.fieldSize,
       phys_size: attractor.physSize,
       rot_offset: 0,
-      spin_mult: attractor.spinDirection
+      spin_mult: attractor.spinDirection,
+      kind: attractor.kind && snake_case(attractor.kind),
+      track: track(attractor.track),
     }
   })
   new_json.zippers = parsed_json.zippers.map(zipper => {
@@ -35,8 +37,84 @@ function js_lvl_to_rust_lvl(js_level) {
       width: zipper.width,
       strength: zipper.strength,
       leading_dist: zipper.leadingDist,
+      track: track(zipper.track),
+    }
+  })
+  new_json.walls = (parsed_json.walls || []).map(wall => {
+    return {
+      points: wall.points.map(point),
+      thickness: wall.thickness,
+      kind: wall.kind && snake_case(wall.kind),
+      restitution: wall.restitution,
+      track: track(wall.track),
+    }
+  })
+  new_json.collectibles = (parsed_json.collectibles || []).map(collectible => {
+    return {
+      pos: point(collectible),
+      kind: collectible.kind && snake_case(collectible.kind),
+      track: track(collectible.track),
+    }
+  })
+  new_json.goal = parsed_json.goal && {
+    pos: point(parsed_json.goal),
+    radius: parsed_json.goal.radius,
+  }
+  new_json.portals = (parsed_json.portals || []).map(portal => {
+    return {
+      a: portal_end(portal.a),
+      b: portal_end(portal.b),
+      keep_velocity: portal.keepVelocity,
+    }
+  })
+  new_json.hazards = (parsed_json.hazards || []).map(hazard => {
+    if (hazard.points) {
+      return {shape: 'polygon', points: hazard.points.map(point)}
+    }
+    return {shape: 'circle', center: point(hazard), radius: hazard.radius}
+  })
+  new_json.boundary = parsed_json.boundary && {
+    min: parsed_json.boundary.min && point(parsed_json.boundary.min),
+    max: parsed_json.boundary.max && point(parsed_json.boundary.max),
+    mode: parsed_json.boundary.mode,
+  }
+  new_json.checkpoints = (parsed_json.checkpoints || []).map(checkpoint => {
+    return {
+      line: {
+        start_point: point(checkpoint.line.startPoint),
+        end_point: point(checkpoint.line.endPoint),
+      },
     }
   })
 
   return JSON.stringify(new_json)
+}
+
+function point(js_point) {
+  return {x: js_point.x, y: js_point.y}
+}
+
+function portal_end(js_end) {
+  return {pos: point(js_end), facing: js_end.facing, width: js_end.width}
+}
+
+// Animation track for a moving object; undefined for a still one.
+function track(js_track) {
+  if (!js_track) {
+    return undefined
+  }
+  return {
+    path: snake_case(js_track.path),
+    to: js_track.to && point(js_track.to),
+    radius: js_track.radius,
+    clockwise: js_track.clockwise,
+    period: js_track.period,
+    phase: js_track.phase,
+    swing: js_track.swing && {pivot: point(js_track.swing.pivot), angle: js_track.swing.angle},
+  }
+}
+
+// "fuelCell" -> "fuel_cell", for enum names.
+function snake_case(name) {
+  return name.replace(/[A-Z]/g, letter => '_' + letter.toLowerCase())
 }
\ No newline at end of file
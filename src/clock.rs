@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+/// Length of one simulation tick; `step` advances a paused clock by this much.
+pub const TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Time scales the debug slow-mo key cycles through.
+pub const TIME_SCALES: [f32; 3] = [1.0, 0.5, 0.25];
+
+/// Whole ticks that fit in `millis` of game time.
+pub fn ticks_in(millis: u64) -> u64 {
+  (millis as u128 * 1_000_000 / TICK.as_nanos()) as u64
+}
+
+/// Game time after `ticks` ticks, to the nearest millisecond.
+pub fn ticks_to_millis(ticks: u64) -> u64 {
+  ((ticks as u128 * TICK.as_nanos() + 500_000) / 1_000_000) as u64
+}
+
+/// Length of one tick in milliseconds, as the physics steps take it.
+pub fn tick_millis() -> f32 {
+  TICK.as_secs_f32() * 1000.0
+}
+
+/// Game time, advanced from the frame delta ggez measures with `Instant`, so
+/// it never runs backwards and can be paused, scaled or single-stepped.
+pub struct GameClock {
+  elapsed: Duration,
+  paused: bool,
+  time_scale: f32,
+  pending_steps: u32,
+}
+
+impl GameClock {
+  pub fn new() -> GameClock {
+    GameClock {
+      elapsed: Duration::from_millis(0),
+      paused: false,
+      time_scale: 1.0,
+      pending_steps: 0,
+    }
+  }
+
+  pub fn advance(
+    &mut self,
+    real_delta: Duration,
+  ) {
+    if self.paused {
+      self.elapsed += TICK * self.pending_steps;
+      self.pending_steps = 0;
+    } else {
+      self.elapsed += real_delta.mul_f32(self.time_scale);
+    }
+  }
+
+  pub fn millis(&self) -> u64 {
+    self.elapsed.as_millis() as u64
+  }
+
+  pub fn elapsed(&self) -> Duration {
+    self.elapsed
+  }
+
+  pub fn pause(&mut self) {
+    self.paused = true;
+  }
+
+  pub fn resume(&mut self) {
+    self.paused = false;
+    self.pending_steps = 0;
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  /// Queues one tick to run on the next `advance`; does nothing unless paused.
+  pub fn step(&mut self) {
+    if self.paused {
+      self.pending_steps += 1;
+    }
+  }
+
+  pub fn time_scale(&self) -> f32 {
+    self.time_scale
+  }
+
+  pub fn set_time_scale(
+    &mut self,
+    time_scale: f32,
+  ) {
+    self.time_scale = time_scale.max(0.0);
+  }
+
+  /// Steps to the next of `TIME_SCALES`, wrapping around.
+  pub fn cycle_time_scale(&mut self) {
+    let current = TIME_SCALES.iter().position(|&scale| scale == self.time_scale).unwrap_or(0);
+    self.time_scale = TIME_SCALES[(current + 1) % TIME_SCALES.len()];
+  }
+}
+
+impl Default for GameClock {
+  fn default() -> GameClock {
+    GameClock::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const FRAME: Duration = Duration::from_millis(20);
+
+  #[test]
+  fn sixty_ticks_a_second() {
+    assert_eq!(ticks_in(1000), 60);
+    assert_eq!(ticks_to_millis(60), 1000);
+    assert_eq!(ticks_in(ticks_to_millis(600) + 1), 600);
+  }
+
+  #[test]
+  fn paused_clock_stands_still() {
+    let mut clock = GameClock::new();
+    clock.advance(FRAME);
+    clock.pause();
+    clock.advance(FRAME);
+    assert!(clock.is_paused());
+    assert_eq!(clock.millis(), 20);
+
+    clock.resume();
+    clock.advance(FRAME);
+    assert_eq!(clock.millis(), 40);
+  }
+
+  #[test]
+  fn step_advances_one_tick_only_while_paused() {
+    let mut clock = GameClock::new();
+    clock.step();
+    clock.advance(FRAME);
+    assert_eq!(clock.elapsed(), FRAME);
+
+    clock.pause();
+    clock.step();
+    clock.step();
+    clock.advance(FRAME);
+    assert_eq!(clock.elapsed(), FRAME + TICK * 2);
+    clock.advance(FRAME);
+    assert_eq!(clock.elapsed(), FRAME + TICK * 2);
+  }
+
+  #[test]
+  fn time_scale_slows_the_clock() {
+    let mut clock = GameClock::new();
+    clock.set_time_scale(0.5);
+    clock.advance(FRAME);
+    assert_eq!(clock.millis(), 10);
+
+    clock.set_time_scale(-1.0);
+    assert_eq!(clock.time_scale(), 0.0);
+  }
+
+  #[test]
+  fn time_scale_cycles() {
+    let mut clock = GameClock::new();
+    for &scale in TIME_SCALES.iter().skip(1).chain(TIME_SCALES.iter().take(1)) {
+      clock.cycle_time_scale();
+      assert_eq!(clock.time_scale(), scale);
+    }
+  }
+}
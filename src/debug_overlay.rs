@@ -0,0 +1,200 @@
+use ggez::graphics::{self, TextFragment};
+use ggez::{Context, GameResult};
+
+use super::game_objects::level::Level;
+use super::geometry::Vec2;
+use super::screen_context::ScreenContext;
+use super::states::StateMachine;
+use super::clock::GameClock;
+use super::drawing_helpers::ui::{Anchor, Layout};
+
+use std::collections::VecDeque;
+
+/// Frames kept for the frame time graph.
+const GRAPH_FRAMES: usize = 120;
+/// Vertical pixels per millisecond in the frame time graph.
+const GRAPH_SCALE: f32 = 3.0;
+/// How far ahead the velocity arrow reaches, in milliseconds of travel.
+const VELOCITY_LOOKAHEAD: f32 = 250.0;
+
+/// Developer view toggled with F3: frame timing, the state stack and the
+/// physics the player normally can't see. Whether it's shown is the
+/// `show_debug` setting.
+pub struct DebugOverlay {
+  frame_times: VecDeque<f32>,
+}
+
+impl DebugOverlay {
+  pub fn new() -> DebugOverlay {
+    DebugOverlay {
+      frame_times: VecDeque::with_capacity(GRAPH_FRAMES),
+    }
+  }
+
+  /// Records how long the last frame took, in milliseconds. Recorded even
+  /// while hidden so the graph is full as soon as it's shown.
+  pub fn record_frame(
+    &mut self,
+    frame_millis: f32,
+  ) {
+    if self.frame_times.len() == GRAPH_FRAMES {
+      self.frame_times.pop_front();
+    }
+    self.frame_times.push_back(frame_millis);
+  }
+
+  pub fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    level: &Level,
+    states: &StateMachine,
+    clock: &GameClock,
+  ) -> GameResult<()> {
+    self.draw_world(ctx, screen, level, states)?;
+    self.draw_graph(ctx, screen)?;
+    self.draw_text(ctx, screen, states, clock)
+  }
+
+  fn draw_world(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    level: &Level,
+    states: &StateMachine,
+  ) -> GameResult<()> {
+    let to_screen = |point: Vec2| screen.point_game_to_screen(point.into());
+    let ship = states.ship();
+
+    let mut mesh = graphics::MeshBuilder::new();
+    let mut drawn = false;
+
+    let ship_cell = ship.map(|ship| level.index.cell_of(ship.pos));
+    for cell in level.index.occupied_cells() {
+      let bounds = level.index.cell_bounds(cell);
+      let top_left = to_screen(bounds.min);
+      let rect = graphics::Rect::new(
+        top_left.x,
+        top_left.y,
+        screen.size_game_to_screen(bounds.width()),
+        screen.size_game_to_screen(bounds.height()),
+      );
+      let color: graphics::Color = if Some(cell) == ship_cell {
+        [0.0, 1.0, 1.0, 0.5].into()
+      } else {
+        [0.0, 1.0, 1.0, 0.12].into()
+      };
+      mesh.rectangle(graphics::DrawMode::stroke(1.0), rect, color)?;
+      drawn = true;
+    }
+
+    for attractor in level.attractors.iter() {
+      let center = to_screen(attractor.pos().into());
+      mesh.circle(graphics::DrawMode::stroke(1.0), center, screen.size_game_to_screen(attractor.field_size()), 0.5, [0.0, 1.0, 0.0, 0.8].into())?;
+      mesh.circle(graphics::DrawMode::stroke(1.0), center, screen.size_game_to_screen(attractor.phys_size()), 0.5, [1.0, 0.2, 0.2, 0.8].into())?;
+      drawn = true;
+    }
+
+    if let Some(ship) = ship {
+      for zipper in level.zippers.iter() {
+        let perp = zipper.get_perp_through_point(&ship.pos.into());
+        let (from, to) = (perp.start_point(), perp.end_point());
+        if Vec2::from(from).approx_eq(to.into(), 1e-3) {
+          continue;
+        }
+        mesh.line(&[screen.point_game_to_screen(from.into()), screen.point_game_to_screen(to.into())], 1.0, [1.0, 1.0, 0.0, 0.6].into())?;
+      }
+
+      let pos = to_screen(ship.pos);
+      mesh.circle(graphics::DrawMode::stroke(1.0), pos, 4.0, 0.5, [1.0, 1.0, 1.0, 1.0].into())?;
+      drawn = true;
+
+      let tip = ship.pos + ship.vel * VELOCITY_LOOKAHEAD;
+      if !tip.approx_eq(ship.pos, 1e-3) {
+        mesh.line(&[pos, to_screen(tip)], 2.0, [1.0, 0.2, 1.0, 1.0].into())?;
+      }
+    }
+
+    if !drawn {
+      return Ok(());
+    }
+
+    let mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+
+  fn draw_graph(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    if self.frame_times.is_empty() {
+      return Ok(());
+    }
+
+    let bottom = screen.size.1 - 10.0;
+    let mut mesh = graphics::MeshBuilder::new();
+
+    for (i, millis) in self.frame_times.iter().enumerate() {
+      let height = (millis * GRAPH_SCALE).min(screen.size.1 / 2.0).max(1.0);
+      let color: graphics::Color = if *millis > 1000.0 / 30.0 {
+        [1.0, 0.2, 0.2, 0.8].into()
+      } else if *millis > 1000.0 / 55.0 {
+        [1.0, 0.8, 0.0, 0.8].into()
+      } else {
+        [0.2, 1.0, 0.2, 0.8].into()
+      };
+      mesh.rectangle(graphics::DrawMode::fill(), graphics::Rect::new(10.0 + i as f32 * 2.0, bottom - height, 2.0, height), color)?;
+    }
+
+    let target = bottom - 1000.0 / 60.0 * GRAPH_SCALE;
+    mesh.line(
+      &[mint::Point2 {x: 10.0, y: target}, mint::Point2 {x: 10.0 + GRAPH_FRAMES as f32 * 2.0, y: target}],
+      1.0,
+      [1.0, 1.0, 1.0, 0.5].into()
+    )?;
+
+    let mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+
+  fn draw_text(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    states: &StateMachine,
+    clock: &GameClock,
+  ) -> GameResult<()> {
+    let frame_millis = self.frame_times.back().copied().unwrap_or(0.0);
+    let average = self.frame_times.iter().sum::<f32>() / self.frame_times.len().max(1) as f32;
+    let (names, state_millis) = states.describe(clock.millis());
+
+    let mut lines = vec![
+      format!("fps: {:.0}  frame: {:.2} ms", 1000.0 / average.max(0.001), frame_millis),
+      format!(
+        "clock: {:.2} s  x{}{}  (F6 pause, F7 step, F8 speed)",
+        clock.millis() as f32 / 1000.0,
+        clock.time_scale(),
+        if clock.is_paused() {"  paused"} else {""}
+      ),
+      format!("state: {}  ({:.2} s)", names.join(" > "), state_millis as f32 / 1000.0),
+    ];
+    if let Some(ship) = states.ship() {
+      lines.push(format!("pos: ({:.1}, {:.1})", ship.pos.x, ship.pos.y));
+      lines.push(format!("vel: ({:.3}, {:.3})  |{:.3}|", ship.vel.x, ship.vel.y, ship.speed()));
+      lines.push(format!("orbit: {}", ship.orbit.map_or("none".to_string(), |orbit| format!("attractor {}", orbit.attractor))));
+    }
+    lines.extend(states.debug_lines());
+
+    let layout = Layout::new(screen);
+    let text = layout.text(TextFragment::new(lines.join("\n")).color([1.0, 1.0, 1.0, 0.9].into()), 16.0);
+
+    layout.draw_text(ctx, &text, Anchor::TopLeft, (10.0, 10.0))
+  }
+}
+
+impl Default for DebugOverlay {
+  fn default() -> DebugOverlay {
+    DebugOverlay::new()
+  }
+}
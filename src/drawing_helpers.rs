@@ -1 +1,4 @@
-pub mod fill_stroke;
\ No newline at end of file
+pub mod fill_stroke;
+pub mod menu;
+pub mod starfield;
+pub mod ui;
\ No newline at end of file
@@ -0,0 +1,59 @@
+use ggez::graphics::{self, TextFragment};
+use ggez::{Context, GameResult};
+
+use super::super::screen_context::ScreenContext;
+use super::ui::{Anchor, Layout};
+
+pub fn draw_dim(
+  ctx: &mut Context,
+  screen: &ScreenContext,
+  alpha: f32,
+) -> GameResult<()> {
+  let dim = graphics::Mesh::new_rectangle(
+    ctx,
+    graphics::DrawMode::fill(),
+    graphics::Rect::new(0.0, 0.0, screen.size.0, screen.size.1),
+    [0.0, 0.0, 0.0, alpha].into()
+  )?;
+  graphics::draw(ctx, &dim, graphics::DrawParam::default())
+}
+
+pub fn draw_options(
+  ctx: &mut Context,
+  screen: &ScreenContext,
+  options: &[&str],
+  cursor: usize,
+) -> GameResult<()> {
+  let layout = Layout::new(screen);
+  let line_height = 40.0;
+  let middle = (options.len() as f32 - 1.0) / 2.0;
+
+  for (i, option) in options.iter().enumerate() {
+    let color: graphics::Color = if i == cursor {
+      [1.0, 1.0, 0.0, 1.0].into()
+    } else {
+      [1.0, 1.0, 1.0, 0.7].into()
+    };
+
+    let text = layout.text(TextFragment::new(*option).color(color), 32.0);
+    layout.draw_text(ctx, &text, Anchor::Center, (0.0, (i as f32 - middle) * line_height))?;
+  }
+
+  Ok(())
+}
+
+pub fn move_cursor(
+  cursor: usize,
+  option_count: usize,
+  up: bool,
+  down: bool,
+) -> usize {
+  let mut cursor = cursor;
+  if up {
+    cursor = (cursor + option_count - 1) % option_count;
+  }
+  if down {
+    cursor = (cursor + 1) % option_count;
+  }
+  cursor
+}
@@ -0,0 +1,152 @@
+use ggez::graphics::{self, spritebatch::SpriteBatch, DrawParam, Image};
+use ggez::{Context, GameResult};
+
+use super::super::screen_context::ScreenContext;
+
+/// Side of the square each layer's stars repeat over, in screen pixels.
+const TILE: f32 = 1024.0;
+/// Side of the dot texture every star is drawn from.
+const DOT_SIZE: u16 = 8;
+
+/// (parallax factor, star count, largest size in pixels, brightness) from
+/// the farthest layer to the nearest.
+const LAYERS: [(f32, usize, f32, f32); 3] = [
+  (0.05, 220, 1.5, 0.45),
+  (0.15, 110, 2.5, 0.7),
+  (0.35, 45, 3.5, 1.0),
+];
+
+struct Star {
+  x: f32,
+  y: f32,
+  size: f32,
+  brightness: f32,
+}
+
+struct Layer {
+  factor: f32,
+  stars: Vec<Star>,
+  batch: Option<SpriteBatch>,
+}
+
+/// Background stars in layers that scroll slower than the level, so they
+/// read as far away. Each layer is built once into a sprite batch and
+/// tiled across the screen.
+pub struct Starfield {
+  seed: u64,
+  layers: Vec<Layer>,
+}
+
+impl Starfield {
+  pub fn new(seed: u64) -> Starfield {
+    let mut rng = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    let mut next = move || {
+      rng ^= rng << 13;
+      rng ^= rng >> 7;
+      rng ^= rng << 17;
+      (rng >> 40) as f32 / (1u64 << 24) as f32
+    };
+
+    let layers = LAYERS.iter().map(|&(factor, count, max_size, brightness)| Layer {
+      factor,
+      stars: (0..count).map(|_| Star {
+        x: next() * TILE,
+        y: next() * TILE,
+        size: max_size * (0.4 + 0.6 * next()),
+        brightness: brightness * (0.5 + 0.5 * next()),
+      }).collect(),
+      batch: None,
+    }).collect();
+
+    Starfield {
+      seed,
+      layers,
+    }
+  }
+
+  pub fn seed(&self) -> u64 {
+    self.seed
+  }
+
+  pub fn draw(
+    &mut self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    if self.layers.iter().any(|layer| layer.batch.is_none()) {
+      let dot = dot_image(ctx)?;
+      for layer in self.layers.iter_mut() {
+        let mut batch = SpriteBatch::new(dot.clone());
+        for star in layer.stars.iter() {
+          let scale = star.size / DOT_SIZE as f32;
+          batch.add(
+            DrawParam::default()
+            .dest(mint::Point2 {x: star.x, y: star.y})
+            .scale(mint::Vector2 {x: scale, y: scale})
+            .color([1.0, 1.0, 1.0, star.brightness].into())
+          );
+        }
+        layer.batch = Some(batch);
+      }
+    }
+
+    for layer in self.layers.iter() {
+      let batch = match layer.batch.as_ref() {
+        Some(batch) => batch,
+        None => continue,
+      };
+
+      let shift_x = (screen.translation.x * screen.scale * layer.factor).rem_euclid(TILE);
+      let shift_y = (screen.translation.y * screen.scale * layer.factor).rem_euclid(TILE);
+
+      let mut y = -shift_y;
+      while y < screen.size.1 {
+        let mut x = -shift_x;
+        while x < screen.size.0 {
+          graphics::draw(ctx, batch, DrawParam::default().dest(mint::Point2 {x, y}))?;
+          x += TILE;
+        }
+        y += TILE;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Soft round white dot.
+fn dot_image(ctx: &mut Context) -> GameResult<Image> {
+  let center = (DOT_SIZE as f32 - 1.0) / 2.0;
+  let mut rgba = Vec::with_capacity(DOT_SIZE as usize * DOT_SIZE as usize * 4);
+
+  for y in 0..DOT_SIZE {
+    for x in 0..DOT_SIZE {
+      let dist = ((x as f32 - center).powi(2) + (y as f32 - center).powi(2)).sqrt() / (center + 0.5);
+      let alpha = (1.0 - dist).clamp(0.0, 1.0);
+      rgba.extend_from_slice(&[255, 255, 255, (alpha * 255.0) as u8]);
+    }
+  }
+
+  Image::from_rgba8(ctx, DOT_SIZE, DOT_SIZE, &rgba)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn positions(starfield: &Starfield) -> Vec<(f32, f32)> {
+    starfield.layers.iter().flat_map(|layer| layer.stars.iter().map(|star| (star.x, star.y))).collect()
+  }
+
+  #[test]
+  fn same_seed_same_sky() {
+    assert_eq!(positions(&Starfield::new(3)), positions(&Starfield::new(3)));
+    assert_ne!(positions(&Starfield::new(3)), positions(&Starfield::new(4)));
+  }
+
+  #[test]
+  fn stars_stay_inside_their_tile() {
+    let starfield = Starfield::new(0);
+    assert!(positions(&starfield).iter().all(|&(x, y)| (0.0..TILE).contains(&x) && (0.0..TILE).contains(&y)));
+  }
+}
@@ -0,0 +1,155 @@
+use ggez::graphics::{self, Text, TextFragment};
+use ggez::{Context, GameResult};
+
+use super::super::screen_context::ScreenContext;
+
+/// Window size the UI is designed at. Font sizes and margins are given in
+/// pixels at this size and scaled to the real window.
+pub const REFERENCE_SIZE: (f32, f32) = (800.0, 600.0);
+
+/// Point of the screen a UI item is pinned to. The same point of the item
+/// lands on it, so `BottomRight` keeps the item's bottom right corner in the
+/// screen's bottom right corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+  TopLeft,
+  Top,
+  TopRight,
+  Left,
+  Center,
+  Right,
+  BottomLeft,
+  Bottom,
+  BottomRight,
+}
+
+impl Anchor {
+  /// How far across and down the anchor sits, from 0 to 1.
+  fn fraction(self) -> (f32, f32) {
+    match self {
+      Anchor::TopLeft => (0.0, 0.0),
+      Anchor::Top => (0.5, 0.0),
+      Anchor::TopRight => (1.0, 0.0),
+      Anchor::Left => (0.0, 0.5),
+      Anchor::Center => (0.5, 0.5),
+      Anchor::Right => (1.0, 0.5),
+      Anchor::BottomLeft => (0.0, 1.0),
+      Anchor::Bottom => (0.5, 1.0),
+      Anchor::BottomRight => (1.0, 1.0),
+    }
+  }
+}
+
+/// Screen-space layout for text and menus, scaled from `REFERENCE_SIZE` so
+/// the UI keeps its proportions at any window size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Layout {
+  pub size: (f32, f32),
+  /// Reference pixels to screen pixels. Follows the tighter axis so
+  /// nothing is pushed off screen in a narrow or short window.
+  pub scale: f32,
+  /// Physical pixels per screen pixel. Text is rasterised at this density
+  /// so it stays sharp on high DPI displays.
+  pub dpi: f32,
+}
+
+impl Layout {
+  pub fn new(screen: &ScreenContext) -> Layout {
+    let scale = (screen.size.0 / REFERENCE_SIZE.0).min(screen.size.1 / REFERENCE_SIZE.1);
+    Layout {
+      size: screen.size,
+      scale: if scale > 0.0 {scale} else {1.0},
+      dpi: screen.dpi.max(1.0),
+    }
+  }
+
+  /// A length in reference pixels, in screen pixels.
+  pub fn px(
+    &self,
+    reference: f32,
+  ) -> f32 {
+    reference * self.scale
+  }
+
+  /// Top left corner for an item `item_size` screen pixels big, pinned to
+  /// `anchor` and moved by `offset` reference pixels (right and down).
+  pub fn place(
+    &self,
+    anchor: Anchor,
+    offset: (f32, f32),
+    item_size: (f32, f32),
+  ) -> mint::Point2<f32> {
+    let (fx, fy) = anchor.fraction();
+    mint::Point2 {
+      x: (self.size.0 - item_size.0) * fx + self.px(offset.0),
+      y: (self.size.1 - item_size.1) * fy + self.px(offset.1),
+    }
+  }
+
+  /// `fragment` as text `size` reference pixels tall.
+  pub fn text(
+    &self,
+    fragment: TextFragment,
+    size: f32,
+  ) -> Text {
+    Text::new(fragment.scale(graphics::PxScale::from(self.px(size) * self.dpi)))
+  }
+
+  /// Size of `text` on screen, in screen pixels.
+  pub fn text_size(
+    &self,
+    ctx: &Context,
+    text: &Text,
+  ) -> (f32, f32) {
+    let dimensions = text.dimensions(ctx);
+    (dimensions.w / self.dpi, dimensions.h / self.dpi)
+  }
+
+  /// Draws `text` pinned to `anchor`, moved by `offset` reference pixels.
+  pub fn draw_text(
+    &self,
+    ctx: &mut Context,
+    text: &Text,
+    anchor: Anchor,
+    offset: (f32, f32),
+  ) -> GameResult<()> {
+    let position = self.place(anchor, offset, self.text_size(ctx, text));
+    graphics::draw(
+      ctx,
+      text,
+      graphics::DrawParam::default()
+      .dest(position)
+      .scale(mint::Vector2 {x: 1.0 / self.dpi, y: 1.0 / self.dpi})
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn layout(width: f32, height: f32) -> Layout {
+    Layout::new(&ScreenContext {size: (width, height), ..ScreenContext::default()})
+  }
+
+  #[test]
+  fn scales_with_the_tighter_axis() {
+    assert_eq!(layout(800.0, 600.0).scale, 1.0);
+    assert_eq!(layout(1600.0, 1200.0).scale, 2.0);
+    assert_eq!(layout(1600.0, 600.0).scale, 1.0);
+    assert_eq!(layout(400.0, 900.0).px(20.0), 10.0);
+  }
+
+  #[test]
+  fn anchors_pin_matching_corners() {
+    let layout = layout(1600.0, 1200.0);
+    assert_eq!(layout.place(Anchor::TopLeft, (10.0, 10.0), (100.0, 50.0)), mint::Point2 {x: 20.0, y: 20.0});
+    assert_eq!(layout.place(Anchor::Center, (0.0, 0.0), (100.0, 50.0)), mint::Point2 {x: 750.0, y: 575.0});
+    assert_eq!(layout.place(Anchor::BottomRight, (-10.0, -10.0), (100.0, 50.0)), mint::Point2 {x: 1480.0, y: 1130.0});
+  }
+
+  #[test]
+  fn empty_screen_falls_back_to_reference_scale() {
+    assert_eq!(layout(0.0, 0.0).scale, 1.0);
+  }
+}
@@ -1,6 +1,17 @@
 pub mod point;
+pub mod animation;
 
 pub mod attractor;
 pub mod zipper;
+pub mod ship;
+pub mod wall;
+pub mod collectible;
+pub mod goal;
+pub mod portal;
+pub mod hazard;
+pub mod boundary;
+pub mod checkpoint;
+pub mod trail;
 
-pub mod level;
\ No newline at end of file
+pub mod level;
+pub mod spatial_index;
\ No newline at end of file
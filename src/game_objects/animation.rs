@@ -0,0 +1,300 @@
+use super::point::Point;
+use super::super::geometry::{Aabb, Vec2};
+
+use serde::Deserialize;
+
+use std::f32::consts::TAU;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "path", rename_all = "snake_case")]
+pub enum Path {
+  /// Slides from the object's level position to `to` (relative), then
+  /// jumps back.
+  Linear { to: Point },
+  /// Slides to `to` (relative) and back again.
+  PingPong { to: Point },
+  /// Circles the object's level position at `radius`.
+  Circular {
+    radius: f32,
+    #[serde(default)]
+    clockwise: bool,
+  },
+  /// Stays at the level position; for tracks that only swing.
+  Fixed,
+}
+
+/// Rotation about a pivot, swinging like a pendulum once per period.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Swing {
+  /// Point the object swings about, in level coordinates.
+  pub pivot: Point,
+  /// Largest angle either side of rest, in degrees.
+  pub angle: f32,
+}
+
+impl Swing {
+  /// Grows `bounds` to cover every angle of the swing.
+  fn sweep(
+    &self,
+    bounds: Aabb,
+  ) -> Aabb {
+    let pivot = Vec2::from(self.pivot);
+    let reach = [
+      bounds.min,
+      bounds.max,
+      Vec2::new(bounds.min.x, bounds.max.y),
+      Vec2::new(bounds.max.x, bounds.min.y),
+    ].iter().map(|corner| corner.distance(pivot)).fold(0.0, f32::max);
+    bounds.union(&Aabb::around(pivot, reach))
+  }
+}
+
+/// Where a track puts its object: rotated by `angle` radians about `pivot`,
+/// then moved by `offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pose {
+  pub offset: Vec2,
+  pub angle: f32,
+  pub pivot: Vec2,
+}
+
+impl Pose {
+  pub const IDENTITY: Pose = Pose {offset: Vec2::ZERO, angle: 0.0, pivot: Vec2::ZERO};
+
+  /// Where a point at `rest` in the level ends up.
+  pub fn apply(
+    &self,
+    rest: Vec2,
+  ) -> Vec2 {
+    if self.angle == 0.0 {
+      return rest + self.offset;
+    }
+    (rest - self.pivot).rotate(self.angle) + self.pivot + self.offset
+  }
+}
+
+impl Default for Pose {
+  fn default() -> Pose {
+    Pose::IDENTITY
+  }
+}
+
+/// Drives an object's offset from its level position, and optionally a
+/// swing about a pivot. Poses are a pure function of simulation time, so
+/// replays and previews line up exactly with the fixed-step simulation.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Track {
+  #[serde(flatten)]
+  pub path: Path,
+  /// Milliseconds per cycle.
+  pub period: f32,
+  /// Fraction of a cycle to start at, 0 to 1.
+  #[serde(default)]
+  pub phase: f32,
+  #[serde(default)]
+  pub swing: Option<Swing>,
+}
+
+impl Track {
+  fn cycle(
+    &self,
+    millis: u64,
+  ) -> f32 {
+    if self.period > 0.0 {
+      ((millis as f64 / self.period as f64) + self.phase as f64).fract() as f32
+    } else {
+      0.0
+    }
+  }
+
+  pub fn offset(
+    &self,
+    millis: u64,
+  ) -> Vec2 {
+    let cycle = self.cycle(millis);
+
+    match self.path {
+      Path::Linear { to } => Vec2::from(to) * cycle,
+      Path::PingPong { to } => {
+        let there_and_back = if cycle < 0.5 {cycle * 2.0} else {2.0 - cycle * 2.0};
+        Vec2::from(to) * there_and_back
+      }
+      Path::Circular { radius, clockwise } => {
+        let angle = cycle * TAU;
+        Vec2::from_angle(if clockwise {angle} else {-angle}) * radius
+      }
+      Path::Fixed => Vec2::ZERO,
+    }
+  }
+
+  /// Swing angle in radians at `millis`; 0 without a swing.
+  pub fn angle(
+    &self,
+    millis: u64,
+  ) -> f32 {
+    match self.swing {
+      Some(swing) => swing.angle.to_radians() * (self.cycle(millis) * TAU).sin(),
+      None => 0.0,
+    }
+  }
+
+  pub fn pose(
+    &self,
+    millis: u64,
+  ) -> Pose {
+    Pose {
+      offset: self.offset(millis),
+      angle: self.angle(millis),
+      pivot: self.swing.map_or(Vec2::ZERO, |swing| swing.pivot.into()),
+    }
+  }
+
+  /// Box around every offset the track can produce.
+  pub fn extent(&self) -> Aabb {
+    match self.path {
+      Path::Linear { to } | Path::PingPong { to } => Aabb::from_points(Vec2::ZERO, to.into()),
+      Path::Circular { radius, .. } => Aabb::around(Vec2::ZERO, radius),
+      Path::Fixed => Aabb::new(Vec2::ZERO, Vec2::ZERO),
+    }
+  }
+
+  /// Grows `bounds` (taken at rest) to cover the whole track.
+  pub fn sweep(
+    &self,
+    bounds: Aabb,
+  ) -> Aabb {
+    let bounds = self.swing.map_or(bounds, |swing| swing.sweep(bounds));
+    let extent = self.extent();
+    Aabb::new(bounds.min + extent.min, bounds.max + extent.max)
+  }
+}
+
+/// Level objects that can follow a `Track`.
+pub trait Animated {
+  fn track(&self) -> Option<&Track>;
+
+  fn set_pose(&mut self, pose: Pose);
+
+  fn animate(
+    &mut self,
+    millis: u64,
+  ) {
+    if let Some(pose) = self.track().map(|track| track.pose(millis)) {
+      self.set_pose(pose);
+    }
+  }
+
+  /// Grows `bounds` (taken at rest) to cover the whole track.
+  fn swept_bounds(
+    &self,
+    bounds: Aabb,
+  ) -> Aabb {
+    match self.track() {
+      Some(track) => track.sweep(bounds),
+      None => bounds,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn close(a: Vec2, b: Vec2) -> bool {
+    a.approx_eq(b, 1e-3)
+  }
+
+  fn to() -> Point {
+    Point {x: 100.0, y: -50.0}
+  }
+
+  #[test]
+  fn linear_wraps_back_to_start() {
+    let track = Track {path: Path::Linear {to: to()}, period: 1000.0, phase: 0.0, swing: None};
+    assert!(close(track.offset(0), Vec2::ZERO));
+    assert!(close(track.offset(500), Vec2::new(50.0, -25.0)));
+    assert!(close(track.offset(1000), Vec2::ZERO));
+  }
+
+  #[test]
+  fn ping_pong_returns() {
+    let track = Track {path: Path::PingPong {to: to()}, period: 1000.0, phase: 0.0, swing: None};
+    assert!(close(track.offset(250), Vec2::new(50.0, -25.0)));
+    assert!(close(track.offset(500), Vec2::new(100.0, -50.0)));
+    assert!(close(track.offset(750), Vec2::new(50.0, -25.0)));
+    assert!(close(track.offset(1000), Vec2::ZERO));
+  }
+
+  #[test]
+  fn circular_keeps_radius_and_direction() {
+    let clockwise = Track {path: Path::Circular {radius: 40.0, clockwise: true}, period: 400.0, phase: 0.0, swing: None};
+    let counter = Track {path: Path::Circular {radius: 40.0, clockwise: false}, period: 400.0, phase: 0.0, swing: None};
+
+    for millis in (0..800).step_by(16) {
+      assert!((clockwise.offset(millis).length() - 40.0).abs() < 1e-3);
+    }
+    assert!(close(clockwise.offset(100), Vec2::new(0.0, 40.0)));
+    assert!(close(counter.offset(100), Vec2::new(0.0, -40.0)));
+  }
+
+  #[test]
+  fn phase_shifts_cycle() {
+    let shifted = Track {path: Path::PingPong {to: to()}, period: 1000.0, phase: 0.5, swing: None};
+    assert!(close(shifted.offset(0), Vec2::new(100.0, -50.0)));
+  }
+
+  #[test]
+  fn offsets_stay_inside_extent() {
+    let tracks = [
+      Track {path: Path::Linear {to: to()}, period: 700.0, phase: 0.3, swing: None},
+      Track {path: Path::PingPong {to: to()}, period: 900.0, phase: 0.0, swing: None},
+      Track {path: Path::Circular {radius: 30.0, clockwise: false}, period: 333.0, phase: 0.1, swing: None},
+    ];
+
+    for track in tracks.iter() {
+      let extent = track.extent().expand(1e-3);
+      for millis in (0..5000).step_by(16) {
+        assert!(extent.contains(track.offset(millis)));
+      }
+    }
+  }
+
+  #[test]
+  fn swing_rotates_about_the_pivot() {
+    let swing = Swing {pivot: Point {x: 0.0, y: 0.0}, angle: 90.0};
+    let track = Track {path: Path::Fixed, period: 1000.0, phase: 0.0, swing: Some(swing)};
+
+    assert!(close(track.pose(0).apply(Vec2::new(100.0, 0.0)), Vec2::new(100.0, 0.0)));
+    assert!(close(track.pose(250).apply(Vec2::new(100.0, 0.0)), Vec2::new(0.0, 100.0)));
+    assert!(close(track.pose(750).apply(Vec2::new(100.0, 0.0)), Vec2::new(0.0, -100.0)));
+    assert!(close(track.pose(500).apply(Vec2::new(100.0, 0.0)), Vec2::new(100.0, 0.0)));
+  }
+
+  #[test]
+  fn swung_points_stay_inside_sweep() {
+    let swing = Swing {pivot: Point {x: -20.0, y: 10.0}, angle: 60.0};
+    let track = Track {path: Path::PingPong {to: to()}, period: 900.0, phase: 0.2, swing: Some(swing)};
+    let rest = Aabb::from_points(Vec2::new(0.0, 0.0), Vec2::new(200.0, 30.0));
+    let swept = track.sweep(rest).expand(1e-3);
+
+    for millis in (0..3000).step_by(16) {
+      let pose = track.pose(millis);
+      assert!(swept.contains(pose.apply(rest.min)));
+      assert!(swept.contains(pose.apply(rest.max)));
+    }
+  }
+
+  #[test]
+  fn parses_from_level_json() {
+    let track: Track = serde_json::from_str(r#"{"path":"ping_pong","to":{"x":0,"y":200},"period":2000,"phase":0.25}"#).unwrap();
+    assert_eq!(track.path, Path::PingPong {to: Point {x: 0.0, y: 200.0}});
+    assert_eq!(track.phase, 0.25);
+
+    let track: Track = serde_json::from_str(r#"{"path":"circular","radius":50,"period":3000}"#).unwrap();
+    assert_eq!(track.path, Path::Circular {radius: 50.0, clockwise: false});
+
+    let track: Track = serde_json::from_str(r#"{"path":"fixed","period":2000,"swing":{"pivot":{"x":0,"y":0},"angle":45}}"#).unwrap();
+    assert_eq!(track.path, Path::Fixed);
+    assert_eq!(track.swing, Some(Swing {pivot: Point {x: 0.0, y: 0.0}, angle: 45.0}));
+  }
+}
@@ -2,11 +2,29 @@ use ggez::{graphics};
 use ggez::{Context, GameResult};
 
 use super::point::Point;
+use super::animation::{Animated, Pose, Track};
 use super::super::screen_context::ScreenContext;
 use super::super::drawing_helpers::fill_stroke::{Fill, Stroke};
+use super::super::geometry::{Aabb, Vec2};
 
 use serde::Deserialize;
 
+/// Acceleration a gravity well or repulsor applies at the edge of its
+/// field, in units per ms². It grows with the inverse square of distance.
+pub const WELL_ACCEL: f32 = 0.0002;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AttractorKind {
+  /// Grabbing inside the field locks the ship into orbit.
+  #[default]
+  Orbit,
+  /// Pulls the ship in, bending its path, but can't be grabbed.
+  GravityWell,
+  /// Pushes the ship away.
+  Repulsor,
+}
+
 #[derive(Deserialize)]
 pub struct Attractor {
   pos: Point,
@@ -14,6 +32,12 @@ pub struct Attractor {
   phys_size: f32,
   rot_offset: f32,
   spin_mult: f32,
+  #[serde(default)]
+  kind: AttractorKind,
+  #[serde(default)]
+  track: Option<Track>,
+  #[serde(skip)]
+  pose: Pose,
 }
 
 impl Attractor {
@@ -28,15 +52,29 @@ impl Attractor {
       field_size: field_radius,
       phys_size: physical_radius,
       rot_offset: 0.0,
-      spin_mult: if spin_clockwise {1.0} else {-1.0}
+      spin_mult: if spin_clockwise {1.0} else {-1.0},
+      kind: AttractorKind::Orbit,
+      track: None,
+      pose: Pose::IDENTITY,
     }
   }
 
+  pub fn with_kind(mut self, kind: AttractorKind) -> Attractor {
+    self.kind = kind;
+    self
+  }
+
+  pub fn with_track(mut self, track: Track) -> Attractor {
+    self.track = Some(track);
+    self
+  }
+
   pub fn in_range(
     &self,
     point: Point
   ) -> bool {
-    let dist = ((self.pos.x - point.x).powf(2.0) + (self.pos.y - point.y).powf(2.0)).sqrt();
+    let pos = self.pos();
+    let dist = ((pos.x - point.x).powf(2.0) + (pos.y - point.y).powf(2.0)).sqrt();
     dist < self.field_size
   }
 
@@ -44,10 +82,75 @@ impl Attractor {
     &self,
     point: Point
   ) -> bool {
-    let dist = ((self.pos.x - point.x).powf(2.0) + (self.pos.y - point.y).powf(2.0)).sqrt();
+    let pos = self.pos();
+    let dist = ((pos.x - point.x).powf(2.0) + (pos.y - point.y).powf(2.0)).sqrt();
     dist < self.phys_size
   }
 
+  /// Current position, including any animation offset.
+  pub fn pos(&self) -> Point {
+    self.pose.apply(self.pos.into()).into()
+  }
+
+  pub fn field_size(&self) -> f32 {
+    self.field_size
+  }
+
+  pub fn phys_size(&self) -> f32 {
+    self.phys_size
+  }
+
+  pub fn spin_mult(&self) -> f32 {
+    self.spin_mult
+  }
+
+  pub fn kind(&self) -> AttractorKind {
+    self.kind
+  }
+
+  /// Whether grabbing inside the field puts the ship into orbit.
+  pub fn captures(&self) -> bool {
+    self.kind == AttractorKind::Orbit
+  }
+
+  /// Acceleration on a free-flying ship at `point`: zero for orbit
+  /// attractors and outside the field, inverse-square inside it. Distance
+  /// is clamped to the body so the pull stays finite.
+  pub fn accel_at(
+    &self,
+    point: Vec2,
+  ) -> Vec2 {
+    let sign = match self.kind {
+      AttractorKind::Orbit => return Vec2::ZERO,
+      AttractorKind::GravityWell => 1.0,
+      AttractorKind::Repulsor => -1.0,
+    };
+
+    let to_center = Vec2::from(self.pos()) - point;
+    let dist = to_center.length();
+    if dist >= self.field_size {
+      return Vec2::ZERO;
+    }
+
+    let direction = match to_center.try_normalize() {
+      Some(direction) => direction,
+      None => return Vec2::ZERO,
+    };
+    let ratio = self.field_size / dist.max(self.phys_size);
+    direction * (sign * WELL_ACCEL * ratio * ratio)
+  }
+
+  /// Everything the attractor can affect: its field or its body, whichever
+  /// is larger.
+  pub fn bounds(&self) -> Aabb {
+    Aabb::around(self.pos().into(), self.field_size.max(self.phys_size))
+  }
+
+  /// `bounds` over the attractor's whole track.
+  pub fn swept_bounds(&self) -> Aabb {
+    Animated::swept_bounds(self, Aabb::around(self.pos.into(), self.field_size.max(self.phys_size)))
+  }
+
   pub fn rotate(
     &mut self,
     time_mult: f32
@@ -64,7 +167,13 @@ impl Attractor {
     ctx: &mut Context,
     screen: &ScreenContext,
   ) -> GameResult<()> {
-    let on_screen_point = screen.point_game_to_screen(self.pos.into());
+    let on_screen_point = screen.point_game_to_screen(self.pos().into());
+
+    let (r, g, b) = match self.kind {
+      AttractorKind::Orbit => (0.0, 1.0, 0.0),
+      AttractorKind::GravityWell => (0.55, 0.35, 1.0),
+      AttractorKind::Repulsor => (1.0, 0.4, 0.1),
+    };
 
     let circle = graphics::Mesh::new_circle(
       ctx,
@@ -72,13 +181,13 @@ impl Attractor {
       on_screen_point,
       self.field_size,
       0.5,
-      [0.0, 1.0, 0.0, 0.157].into()
+      [r, g, b, 0.157].into()
     )?;
 
 
     let dashed_stroke = (
       graphics::StrokeOptions::default().with_line_width(3.0),
-      [0.0, 1.0, 0.0, 0.275].into()
+      [r, g, b, 0.275].into()
     );
 
     let dashed_circle = dashed_circle_stroke(
@@ -119,6 +228,16 @@ impl Attractor {
   }
 }
 
+impl Animated for Attractor {
+  fn track(&self) -> Option<&Track> {
+    self.track.as_ref()
+  }
+
+  fn set_pose(&mut self, pose: Pose) {
+    self.pose = pose;
+  }
+}
+
 fn dashed_circle_stroke(
   ctx: &mut Context,
   stroke: Stroke,
@@ -179,11 +298,10 @@ fn spiky_circle(
         points.push(mint::Point2 {x: x_pos, y: y_pos});
       }
     }
-    let first_point;
-    match points.first() {
-      Some(p) => first_point = *p,
-      None => first_point = mint::Point2 {x: 0.0, y: 0.0}
-    }
+    let first_point = match points.first() {
+      Some(p) => *p,
+      None => mint::Point2 {x: 0.0, y: 0.0}
+    };
     points.push(first_point);
 
     mesh.polyline(
@@ -194,4 +312,90 @@ fn spiky_circle(
   }
 
   mesh.build(ctx)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use proptest::prelude::*;
+
+  fn attractor() -> Attractor {
+    Attractor::new(&Point {x: 10.0, y: -20.0}, 150.0, 50.0, true)
+  }
+
+  #[test]
+  fn in_range_inside_and_outside() {
+    let a = attractor();
+    assert!(a.in_range(Point {x: 10.0, y: -20.0}));
+    assert!(a.in_range(Point {x: 100.0, y: -20.0}));
+    assert!(!a.in_range(Point {x: 200.0, y: -20.0}));
+    assert!(!a.in_range(Point {x: 10.0, y: 130.0}));
+  }
+
+  #[test]
+  fn collided_uses_physical_radius() {
+    let a = attractor();
+    assert!(a.collided(Point {x: 10.0, y: 20.0}));
+    assert!(!a.collided(Point {x: 10.0, y: 40.0}));
+    assert!(a.in_range(Point {x: 10.0, y: 40.0}));
+  }
+
+  #[test]
+  fn kind_defaults_to_orbit() {
+    let a: Attractor = serde_json::from_str(
+      r#"{"pos": {"x": 0.0, "y": 0.0}, "field_size": 150.0, "phys_size": 50.0, "rot_offset": 0.0, "spin_mult": 1.0}"#
+    ).unwrap();
+    assert_eq!(a.kind(), AttractorKind::Orbit);
+    assert!(a.captures());
+
+    let a: Attractor = serde_json::from_str(
+      r#"{"pos": {"x": 0.0, "y": 0.0}, "field_size": 150.0, "phys_size": 50.0, "rot_offset": 0.0, "spin_mult": 1.0, "kind": "gravity_well"}"#
+    ).unwrap();
+    assert_eq!(a.kind(), AttractorKind::GravityWell);
+    assert!(!a.captures());
+  }
+
+  #[test]
+  fn wells_pull_and_repulsors_push() {
+    let point = Vec2::new(110.0, -20.0);
+
+    assert_eq!(attractor().accel_at(point), Vec2::ZERO);
+
+    let pull = attractor().with_kind(AttractorKind::GravityWell).accel_at(point);
+    assert!(pull.x < 0.0);
+    assert!(pull.y.abs() < 1e-6);
+
+    let push = attractor().with_kind(AttractorKind::Repulsor).accel_at(point);
+    assert!(push.approx_eq(-pull, 1e-6));
+
+    let outside = attractor().with_kind(AttractorKind::GravityWell).accel_at(Vec2::new(200.0, -20.0));
+    assert_eq!(outside, Vec2::ZERO);
+  }
+
+  #[test]
+  fn well_pull_falls_off_with_inverse_square() {
+    let well = attractor().with_kind(AttractorKind::GravityWell);
+    let near = well.accel_at(Vec2::new(70.0, -20.0)).length();
+    let far = well.accel_at(Vec2::new(130.0, -20.0)).length();
+    assert!((near / far - 4.0).abs() < 1e-3);
+  }
+
+  proptest! {
+    #[test]
+    fn in_range_matches_distance(angle in 0.0f32..std::f32::consts::TAU, dist in 0.0f32..400.0) {
+      prop_assume!((dist - 150.0).abs() > 1e-2);
+
+      let a = attractor();
+      let point = Point {x: 10.0 + angle.cos() * dist, y: -20.0 + angle.sin() * dist};
+      prop_assert_eq!(a.in_range(point), dist < 150.0);
+    }
+
+    #[test]
+    fn collision_implies_in_range(x in -300.0f32..300.0, y in -300.0f32..300.0) {
+      let a = attractor();
+      let point = Point {x, y};
+      prop_assert!(!a.collided(point) || a.in_range(point));
+    }
+  }
+}
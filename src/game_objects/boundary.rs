@@ -0,0 +1,157 @@
+use ggez::{graphics};
+use ggez::{Context, GameResult};
+
+use super::point::Point;
+use super::super::geometry::{Aabb, Segment, Vec2};
+use super::super::screen_context::ScreenContext;
+
+use serde::Deserialize;
+
+/// Space left around the level's objects when no explicit boundary is given.
+pub const AUTO_MARGIN: f32 = 600.0;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundaryMode {
+  /// Leaving the arena destroys the ship.
+  #[default]
+  Kill,
+  /// Leaving one side brings the ship back in on the opposite side.
+  Wrap,
+}
+
+/// Edge of the playable area. Without `min`/`max` it is fitted around the
+/// level's objects with `AUTO_MARGIN` to spare.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Boundary {
+  #[serde(default)]
+  pub min: Option<Point>,
+  #[serde(default)]
+  pub max: Option<Point>,
+  #[serde(default)]
+  pub mode: BoundaryMode,
+}
+
+impl Boundary {
+  /// The arena this boundary encloses, given the box around the level's
+  /// objects. An explicit rect with no width or height encloses nothing and
+  /// gives no arena.
+  pub fn arena(
+    &self,
+    level_bounds: Option<Aabb>,
+  ) -> Option<Aabb> {
+    match (self.min, self.max) {
+      (Some(min), Some(max)) => Some(Aabb::from_points(min.into(), max.into()))
+        .filter(|arena| arena.width() > 0.0 && arena.height() > 0.0),
+      _ => level_bounds.map(|bounds| bounds.expand(AUTO_MARGIN)),
+    }
+  }
+}
+
+/// Brings `point` back into `arena` from the opposite side.
+pub fn wrap(
+  arena: &Aabb,
+  point: Vec2,
+) -> Vec2 {
+  Vec2::new(
+    arena.min.x + (point.x - arena.min.x).rem_euclid(arena.width()),
+    arena.min.y + (point.y - arena.min.y).rem_euclid(arena.height()),
+  )
+}
+
+/// Where `motion`, starting inside `arena`, first crosses its edge. The end
+/// of `motion` if it never leaves.
+pub fn exit_point(
+  arena: &Aabb,
+  motion: &Segment,
+) -> Vec2 {
+  let delta = motion.end - motion.start;
+  let exit_along = |start: f32, delta: f32, min: f32, max: f32| {
+    if delta > 0.0 {
+      (max - start) / delta
+    } else if delta < 0.0 {
+      (min - start) / delta
+    } else {
+      1.0
+    }
+  };
+  let t = exit_along(motion.start.x, delta.x, arena.min.x, arena.max.x)
+    .min(exit_along(motion.start.y, delta.y, arena.min.y, arena.max.y))
+    .clamp(0.0, 1.0);
+  motion.point_at(t)
+}
+
+pub fn draw_arena(
+  ctx: &mut Context,
+  screen: &ScreenContext,
+  arena: &Aabb,
+  mode: BoundaryMode,
+) -> GameResult<()> {
+  let top_left = screen.point_game_to_screen(arena.min.into());
+  let rect = graphics::Rect::new(
+    top_left.x,
+    top_left.y,
+    screen.size_game_to_screen(arena.width()),
+    screen.size_game_to_screen(arena.height()),
+  );
+
+  let color: graphics::Color = match mode {
+    BoundaryMode::Kill => [1.0, 0.2, 0.2, 0.6].into(),
+    BoundaryMode::Wrap => [0.3, 0.6, 1.0, 0.6].into(),
+  };
+
+  let mesh = graphics::Mesh::new_rectangle(
+    ctx,
+    graphics::DrawMode::stroke(screen.size_game_to_screen(6.0).max(1.0)),
+    rect,
+    color
+  )?;
+  graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn explicit_rect_beats_auto_fit() {
+    let level_bounds = Some(Aabb::new(Vec2::new(-100.0, -100.0), Vec2::new(100.0, 100.0)));
+
+    let auto = Boundary::default().arena(level_bounds).unwrap();
+    assert_eq!(auto.min, Vec2::new(-100.0 - AUTO_MARGIN, -100.0 - AUTO_MARGIN));
+
+    let explicit = Boundary {
+      min: Some(Point {x: -50.0, y: -60.0}),
+      max: Some(Point {x: 50.0, y: 60.0}),
+      mode: BoundaryMode::Wrap,
+    };
+    assert_eq!(explicit.arena(level_bounds).unwrap().max, Vec2::new(50.0, 60.0));
+    assert_eq!(Boundary::default().arena(None), None);
+  }
+
+  #[test]
+  fn flat_rect_gives_no_arena() {
+    let flat = Boundary {
+      min: Some(Point {x: -50.0, y: 10.0}),
+      max: Some(Point {x: 50.0, y: 10.0}),
+      mode: BoundaryMode::Wrap,
+    };
+    assert_eq!(flat.arena(None), None);
+  }
+
+  #[test]
+  fn exit_point_is_on_the_crossed_edge() {
+    let arena = Aabb::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 50.0));
+    let motion = Segment::new(Vec2::new(80.0, 20.0), Vec2::new(120.0, 30.0));
+    assert!(exit_point(&arena, &motion).approx_eq(Vec2::new(100.0, 25.0), 1e-4));
+    let inside = Segment::new(Vec2::new(10.0, 10.0), Vec2::new(20.0, 20.0));
+    assert!(exit_point(&arena, &inside).approx_eq(inside.end, 1e-4));
+  }
+
+  #[test]
+  fn wrap_comes_back_on_the_far_side() {
+    let arena = Aabb::new(Vec2::new(0.0, 0.0), Vec2::new(100.0, 50.0));
+    assert!(wrap(&arena, Vec2::new(105.0, 10.0)).approx_eq(Vec2::new(5.0, 10.0), 1e-4));
+    assert!(wrap(&arena, Vec2::new(20.0, -3.0)).approx_eq(Vec2::new(20.0, 47.0), 1e-4));
+  }
+}
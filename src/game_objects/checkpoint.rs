@@ -0,0 +1,124 @@
+use ggez::{graphics};
+use ggez::{Context, GameResult};
+
+use super::point::Point;
+use super::zipper::DirectionalLine;
+use super::super::geometry::Aabb;
+use super::super::screen_context::ScreenContext;
+
+use serde::Deserialize;
+
+/// A gate the ship has to fly through. Gates count only in level order.
+#[derive(Deserialize)]
+pub struct Checkpoint {
+  line: DirectionalLine,
+  #[serde(skip)]
+  passed: bool,
+}
+
+impl Checkpoint {
+  pub fn new(
+    p_start: &Point,
+    p_end: &Point,
+  ) -> Checkpoint {
+    Checkpoint {
+      line: DirectionalLine::new(p_start, p_end),
+      passed: false,
+    }
+  }
+
+  pub fn line(&self) -> &DirectionalLine {
+    &self.line
+  }
+
+  pub fn is_passed(&self) -> bool {
+    self.passed
+  }
+
+  pub fn set_passed(
+    &mut self,
+    passed: bool,
+  ) {
+    self.passed = passed;
+  }
+
+  /// Where the ship crosses the gate moving along `motion`, if it does.
+  pub fn crossed_by(
+    &self,
+    motion: &DirectionalLine,
+  ) -> Option<Point> {
+    self.line.calc_line_seg_intersect(motion)
+  }
+
+  pub fn bounds(&self) -> Aabb {
+    Aabb::from_points(self.line.start_point().into(), self.line.end_point().into())
+  }
+
+  /// `is_next` highlights the gate the ship is heading for.
+  pub fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    is_next: bool,
+  ) -> GameResult<()> {
+    let color: graphics::Color = if self.passed {
+      [0.3, 1.0, 0.4, 0.5].into()
+    } else if is_next {
+      [0.2, 0.9, 1.0, 1.0].into()
+    } else {
+      [1.0, 1.0, 1.0, 0.3].into()
+    };
+
+    let start = screen.point_game_to_screen(self.line.start_point().into());
+    let end = screen.point_game_to_screen(self.line.end_point().into());
+
+    let mesh = graphics::MeshBuilder::new()
+      .line(&[start, end], screen.size_game_to_screen(4.0).max(1.0), color)?
+      .circle(graphics::DrawMode::fill(), start, screen.size_game_to_screen(10.0), 0.5, color)?
+      .circle(graphics::DrawMode::fill(), end, screen.size_game_to_screen(10.0), 0.5, color)?
+      .build(ctx)?;
+
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn crossed_by_uses_the_motion_segment() {
+    let gate = Checkpoint::new(&Point {x: 0.0, y: -50.0}, &Point {x: 0.0, y: 50.0});
+
+    let through = DirectionalLine::new(&Point {x: -10.0, y: 5.0}, &Point {x: 10.0, y: 5.0});
+    let crossing = gate.crossed_by(&through).unwrap();
+    assert!(crossing.x.abs() < 1e-4 && (crossing.y - 5.0).abs() < 1e-4);
+
+    let short = DirectionalLine::new(&Point {x: -10.0, y: 5.0}, &Point {x: -1.0, y: 5.0});
+    assert!(gate.crossed_by(&short).is_none());
+
+    let around = DirectionalLine::new(&Point {x: -10.0, y: 60.0}, &Point {x: 10.0, y: 60.0});
+    assert!(gate.crossed_by(&around).is_none());
+  }
+
+  #[test]
+  fn level_only_counts_gates_in_order() {
+    use super::super::level::Level;
+    use super::super::super::geometry::{Segment, Vec2};
+
+    let mut level = Level::default();
+    level.checkpoints.push(Checkpoint::new(&Point {x: 100.0, y: -50.0}, &Point {x: 100.0, y: 50.0}));
+    level.checkpoints.push(Checkpoint::new(&Point {x: 0.0, y: -50.0}, &Point {x: 0.0, y: 50.0}));
+
+    let through_second = Segment::new(Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0));
+    assert_eq!(level.pass_checkpoint_along(&through_second), None);
+
+    let through_first = Segment::new(Vec2::new(90.0, 0.0), Vec2::new(110.0, 0.0));
+    assert_eq!(level.pass_checkpoint_along(&through_first), Some(0));
+    assert_eq!(level.pass_checkpoint_along(&through_second), Some(1));
+    assert_eq!(level.next_checkpoint(), None);
+
+    level.restore(&[], 1);
+    assert_eq!(level.next_checkpoint(), Some(1));
+  }
+}
@@ -0,0 +1,176 @@
+use ggez::{graphics};
+use ggez::{Context, GameResult};
+
+use super::point::Point;
+use super::animation::{Animated, Pose, Track};
+use super::super::geometry::{Aabb, Segment, Vec2};
+use super::super::screen_context::ScreenContext;
+
+use serde::Deserialize;
+
+/// How close the ship's centre must pass to pick something up, on top of
+/// its own radius.
+pub const PICKUP_RADIUS: f32 = 20.0;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectibleKind {
+  #[default]
+  Star,
+  FuelCell,
+}
+
+/// A pickup worth `value` points, collected by flying through it. Pickups
+/// come back every attempt.
+#[derive(Deserialize)]
+pub struct Collectible {
+  pos: Point,
+  #[serde(default)]
+  kind: CollectibleKind,
+  #[serde(default)]
+  track: Option<Track>,
+  #[serde(skip)]
+  pose: Pose,
+  #[serde(skip)]
+  collected: bool,
+}
+
+impl Collectible {
+  pub fn new(
+    pos: &Point,
+    kind: CollectibleKind,
+  ) -> Collectible {
+    Collectible {
+      pos: *pos,
+      kind,
+      track: None,
+      pose: Pose::IDENTITY,
+      collected: false,
+    }
+  }
+
+  pub fn with_track(mut self, track: Track) -> Collectible {
+    self.track = Some(track);
+    self
+  }
+
+  /// Current position, including any animation offset.
+  pub fn pos(&self) -> Point {
+    self.pose.apply(self.pos.into()).into()
+  }
+
+  pub fn kind(&self) -> CollectibleKind {
+    self.kind
+  }
+
+  pub fn value(&self) -> u32 {
+    match self.kind {
+      CollectibleKind::Star => 100,
+      CollectibleKind::FuelCell => 50,
+    }
+  }
+
+  pub fn is_collected(&self) -> bool {
+    self.collected
+  }
+
+  pub fn set_collected(
+    &mut self,
+    collected: bool,
+  ) {
+    self.collected = collected;
+  }
+
+  /// Whether a ship moving along `motion` passes close enough to pick this up.
+  pub fn touched_by(
+    &self,
+    motion: &Segment,
+    ship_radius: f32,
+  ) -> bool {
+    motion.sweep_circle(self.pos().into(), PICKUP_RADIUS + ship_radius).is_some()
+  }
+
+  pub fn bounds(&self) -> Aabb {
+    Aabb::around(self.pos().into(), PICKUP_RADIUS)
+  }
+
+  /// `bounds` over the pickup's whole track.
+  pub fn swept_bounds(&self) -> Aabb {
+    Animated::swept_bounds(self, Aabb::around(self.pos.into(), PICKUP_RADIUS))
+  }
+
+  pub fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    if self.collected {
+      return Ok(());
+    }
+
+    let center: Vec2 = self.pos().into();
+    let size = PICKUP_RADIUS * 0.8;
+
+    let mesh = match self.kind {
+      CollectibleKind::Star => {
+        let points: Vec<mint::Point2<f32>> = (0..10).map(|i| {
+          let radius = if i % 2 == 0 {size} else {size * 0.45};
+          let angle = i as f32 / 10.0 * std::f32::consts::TAU - std::f32::consts::FRAC_PI_2;
+          screen.point_game_to_screen((center + Vec2::from_angle(angle) * radius).into())
+        }).collect();
+
+        graphics::Mesh::new_polygon(
+          ctx,
+          graphics::DrawMode::fill(),
+          &points,
+          [1.0, 0.85, 0.1, 1.0].into()
+        )?
+      }
+      CollectibleKind::FuelCell => {
+        let top_left = screen.point_game_to_screen((center - Vec2::new(size * 0.5, size)).into());
+
+        graphics::Mesh::new_rounded_rectangle(
+          ctx,
+          graphics::DrawMode::fill(),
+          graphics::Rect::new(top_left.x, top_left.y, screen.size_game_to_screen(size), screen.size_game_to_screen(size * 2.0)),
+          screen.size_game_to_screen(size * 0.3),
+          [0.3, 1.0, 0.6, 1.0].into()
+        )?
+      }
+    };
+
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+}
+
+impl Animated for Collectible {
+  fn track(&self) -> Option<&Track> {
+    self.track.as_ref()
+  }
+
+  fn set_pose(&mut self, pose: Pose) {
+    self.pose = pose;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn kind_defaults_to_star() {
+    let c: Collectible = serde_json::from_str(r#"{"pos": {"x": 1.0, "y": 2.0}}"#).unwrap();
+    assert_eq!(c.kind(), CollectibleKind::Star);
+    assert_eq!(c.value(), 100);
+    assert!(!c.is_collected());
+  }
+
+  #[test]
+  fn touched_by_sweeps_the_whole_motion() {
+    let c = Collectible::new(&Point {x: 0.0, y: 0.0}, CollectibleKind::FuelCell);
+
+    // Jumps right over the pickup in a single step.
+    assert!(c.touched_by(&Segment::new(Vec2::new(-500.0, 10.0), Vec2::new(500.0, 10.0)), 8.0));
+    assert!(!c.touched_by(&Segment::new(Vec2::new(-500.0, 40.0), Vec2::new(500.0, 40.0)), 8.0));
+  }
+}
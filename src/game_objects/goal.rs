@@ -0,0 +1,64 @@
+use ggez::{graphics};
+use ggez::{Context, GameResult};
+
+use super::point::Point;
+use super::super::geometry::{Aabb, Segment};
+use super::super::screen_context::ScreenContext;
+
+use serde::Deserialize;
+
+/// The level's finish: flying into it wins the run.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Goal {
+  pos: Point,
+  radius: f32,
+}
+
+impl Goal {
+  pub fn new(
+    pos: &Point,
+    radius: f32,
+  ) -> Goal {
+    Goal {
+      pos: *pos,
+      radius,
+    }
+  }
+
+  pub fn pos(&self) -> Point {
+    self.pos
+  }
+
+  pub fn radius(&self) -> f32 {
+    self.radius
+  }
+
+  /// Whether a ship moving along `motion` enters the goal.
+  pub fn reached_by(
+    &self,
+    motion: &Segment,
+  ) -> bool {
+    motion.sweep_circle(self.pos.into(), self.radius).is_some()
+  }
+
+  pub fn bounds(&self) -> Aabb {
+    Aabb::around(self.pos.into(), self.radius)
+  }
+
+  pub fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    let center = screen.point_game_to_screen(self.pos.into());
+    let radius = screen.size_game_to_screen(self.radius);
+
+    let mesh = graphics::MeshBuilder::new()
+      .circle(graphics::DrawMode::fill(), center, radius, 0.5, [1.0, 1.0, 1.0, 0.12].into())?
+      .circle(graphics::DrawMode::stroke(3.0), center, radius, 0.5, [1.0, 1.0, 1.0, 0.8].into())?
+      .circle(graphics::DrawMode::stroke(2.0), center, radius * 0.6, 0.5, [1.0, 1.0, 1.0, 0.5].into())?
+      .build(ctx)?;
+
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+}
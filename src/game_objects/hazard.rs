@@ -0,0 +1,172 @@
+use ggez::{graphics};
+use ggez::{Context, GameResult};
+
+use super::point::Point;
+use super::super::geometry::{Aabb, Segment, Vec2};
+use super::super::screen_context::ScreenContext;
+
+use serde::Deserialize;
+
+/// A static region that destroys the ship on contact.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+pub enum Hazard {
+  Circle { center: Point, radius: f32 },
+  /// Simple polygon; the last point joins back to the first.
+  Polygon { points: Vec<Point> },
+}
+
+impl Hazard {
+  pub fn edges(&self) -> Vec<Segment> {
+    match self {
+      Hazard::Circle { .. } => Vec::new(),
+      Hazard::Polygon { points } => (0..points.len())
+        .map(|i| Segment::new(points[i].into(), points[(i + 1) % points.len()].into()))
+        .collect(),
+    }
+  }
+
+  pub fn contains(
+    &self,
+    point: Vec2,
+  ) -> bool {
+    match self {
+      Hazard::Circle { center, radius } => point.distance((*center).into()) < *radius,
+      Hazard::Polygon { .. } => {
+        // Even-odd rule: count edges crossed by a ray heading in +x.
+        self.edges().iter().filter(|edge| {
+          (edge.start.y > point.y) != (edge.end.y > point.y) &&
+          point.x < edge.start.x + (point.y - edge.start.y) / (edge.end.y - edge.start.y) * (edge.end.x - edge.start.x)
+        }).count() % 2 == 1
+      }
+    }
+  }
+
+  /// Earliest parameter along `motion` at which a ship of `ship_radius`
+  /// touches the hazard; 0 if it starts inside.
+  pub fn hit_along(
+    &self,
+    motion: &Segment,
+    ship_radius: f32,
+  ) -> Option<f32> {
+    match self {
+      Hazard::Circle { center, radius } => motion.sweep_circle((*center).into(), radius + ship_radius),
+      Hazard::Polygon { .. } => {
+        if self.contains(motion.start) {
+          return Some(0.0);
+        }
+        self.edges().iter()
+          .filter_map(|edge| motion.sweep_capsule(edge, ship_radius))
+          .fold(None, |earliest: Option<f32>, t| Some(earliest.map_or(t, |e| e.min(t))))
+      }
+    }
+  }
+
+  pub fn bounds(&self) -> Aabb {
+    match self {
+      Hazard::Circle { center, radius } => Aabb::around((*center).into(), *radius),
+      Hazard::Polygon { points } => points.iter()
+        .map(|point| Aabb::from_points((*point).into(), (*point).into()))
+        .reduce(|a, b| a.union(&b))
+        .unwrap_or_else(|| Aabb::around(Vec2::ZERO, 0.0)),
+    }
+  }
+
+  pub fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    let fill: graphics::Color = [0.7, 0.05, 0.1, 0.35].into();
+    let edge: graphics::Color = [1.0, 0.75, 0.0, 0.9].into();
+    let line_width = screen.size_game_to_screen(4.0).max(1.0);
+
+    let mut mesh = graphics::MeshBuilder::new();
+
+    match self {
+      Hazard::Circle { center, radius } => {
+        let center = screen.point_game_to_screen((*center).into());
+        let radius = screen.size_game_to_screen(*radius);
+        mesh.circle(graphics::DrawMode::fill(), center, radius, 0.5, fill)?;
+        mesh.circle(graphics::DrawMode::stroke(line_width), center, radius, 0.5, edge)?;
+      }
+      Hazard::Polygon { points } => {
+        if points.len() < 3 {
+          return Ok(());
+        }
+        let points: Vec<mint::Point2<f32>> = points.iter()
+          .map(|point| screen.point_game_to_screen((*point).into()))
+          .collect();
+        mesh.polygon(graphics::DrawMode::fill(), &points, fill)?;
+        mesh.polygon(graphics::DrawMode::stroke(line_width), &points, edge)?;
+      }
+    }
+
+    // Warning stripes across the bounding box, clipped to the shape by
+    // only drawing the parts whose midpoints fall inside.
+    let bounds = self.bounds();
+    let spacing = 40.0;
+    let stripe_count = ((bounds.width() + bounds.height()) / spacing).ceil() as i32;
+    for i in 0..stripe_count {
+      let offset = i as f32 * spacing;
+      let stripe = Segment::new(
+        Vec2::new(bounds.min.x + offset, bounds.min.y),
+        Vec2::new(bounds.min.x + offset - bounds.height(), bounds.max.y),
+      );
+      let pieces = 16;
+      for j in 0..pieces {
+        let from = stripe.point_at(j as f32 / pieces as f32);
+        let to = stripe.point_at((j + 1) as f32 / pieces as f32);
+        if self.contains(from.lerp(to, 0.5)) {
+          mesh.line(
+            &[screen.point_game_to_screen(from.into()), screen.point_game_to_screen(to.into())],
+            line_width,
+            [1.0, 0.75, 0.0, 0.3].into()
+          )?;
+        }
+      }
+    }
+
+    let mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn triangle() -> Hazard {
+    Hazard::Polygon {points: vec![
+      Point {x: 0.0, y: 0.0},
+      Point {x: 100.0, y: 0.0},
+      Point {x: 0.0, y: 100.0},
+    ]}
+  }
+
+  #[test]
+  fn polygon_contains_uses_even_odd_rule() {
+    let hazard = triangle();
+    assert!(hazard.contains(Vec2::new(20.0, 20.0)));
+    assert!(!hazard.contains(Vec2::new(80.0, 80.0)));
+    assert!(!hazard.contains(Vec2::new(-5.0, 20.0)));
+  }
+
+  #[test]
+  fn hit_along_sweeps_edges_and_circles() {
+    let motion = Segment::new(Vec2::new(-100.0, 20.0), Vec2::new(100.0, 20.0));
+
+    let t = triangle().hit_along(&motion, 8.0).unwrap();
+    assert!((motion.point_at(t).x - -8.0).abs() < 1e-2);
+
+    let circle = Hazard::Circle {center: Point {x: 0.0, y: 0.0}, radius: 10.0};
+    assert!(circle.hit_along(&motion, 8.0).is_none());
+    assert!(circle.hit_along(&motion, 12.0).is_some());
+  }
+
+  #[test]
+  fn parses_tagged_shapes() {
+    let hazard: Hazard = serde_json::from_str(r#"{"shape": "circle", "center": {"x": 1.0, "y": 2.0}, "radius": 3.0}"#).unwrap();
+    assert_eq!(hazard, Hazard::Circle {center: Point {x: 1.0, y: 2.0}, radius: 3.0});
+  }
+}
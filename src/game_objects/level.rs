@@ -1,17 +1,69 @@
 use super::super::get_resource_folder;
 use super::zipper::Zipper;
 use super::attractor::Attractor;
+use super::wall::Wall;
+use super::collectible::Collectible;
+use super::goal::Goal;
+use super::portal::Portal;
+use super::hazard::Hazard;
+use super::boundary::{self, Boundary};
+use super::checkpoint::Checkpoint;
+use super::zipper::DirectionalLine;
+use super::ship::SHIP_RADIUS;
+use super::animation::Animated;
+use super::spatial_index::{ObjectRef, SpatialIndex, DEFAULT_CELL_SIZE};
 
 use super::super::screen_context::ScreenContext;
+use super::super::particles::ParticleSystem;
+use super::super::geometry::{Aabb, Segment, Vec2};
 
 use ggez::{GameResult, Context};
 
 use serde::Deserialize;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 pub struct Level {
   pub attractors: Vec<Attractor>,
   pub zippers: Vec<Zipper>,
+  #[serde(default)]
+  pub walls: Vec<Wall>,
+  #[serde(default)]
+  pub collectibles: Vec<Collectible>,
+  #[serde(default)]
+  pub portals: Vec<Portal>,
+  #[serde(default)]
+  pub goal: Option<Goal>,
+  #[serde(default)]
+  pub hazards: Vec<Hazard>,
+  #[serde(default)]
+  pub boundary: Boundary,
+  #[serde(default)]
+  pub checkpoints: Vec<Checkpoint>,
+
+  /// Resolved from `boundary` whenever the index is rebuilt.
+  #[serde(skip)]
+  pub arena: Option<Aabb>,
+
+  /// Index of this level in the file it was loaded from.
+  #[serde(skip)]
+  pub number: usize,
+
+  /// File this level was loaded from, and how many levels it holds.
+  #[serde(skip)]
+  pub source: Option<std::path::PathBuf>,
+  #[serde(skip)]
+  pub level_count: usize,
+
+  /// Simulation time of the last `animate`.
+  #[serde(skip)]
+  pub millis: u64,
+
+  /// Effects layer, drawn over the ship by the states that use it.
+  #[serde(skip)]
+  pub particles: ParticleSystem,
+
+  #[serde(skip)]
+  pub index: SpatialIndex,
 }
 
 impl Level {
@@ -20,30 +72,259 @@ impl Level {
     ctx: &mut Context,
     screen: &ScreenContext
   ) -> GameResult<()> {
+    if let Some(arena) = self.arena.as_ref() {
+      boundary::draw_arena(ctx, screen, arena, self.boundary.mode)?;
+    }
+    for hazard in self.hazards.iter() {
+      hazard.draw(ctx, screen)?;
+    }
+    let next_checkpoint = self.next_checkpoint();
+    for (i, checkpoint) in self.checkpoints.iter().enumerate() {
+      checkpoint.draw(ctx, screen, Some(i) == next_checkpoint)?;
+    }
+    if let Some(goal) = self.goal.as_ref() {
+      goal.draw(ctx, screen)?;
+    }
+    for wall in self.walls.iter() {
+      wall.draw(ctx, screen)?;
+    }
     for zipper in self.zippers.iter() {
-      zipper.draw(ctx, screen)?;
+      zipper.draw(ctx, screen, self.millis)?;
+    }
+    for portal in self.portals.iter() {
+      portal.draw(ctx, screen)?;
     }
     for attractor in self.attractors.iter() {
       attractor.draw(ctx, screen)?;
     }
+    for collectible in self.collectibles.iter() {
+      collectible.draw(ctx, screen)?;
+    }
     Ok(())
   }
 
+  /// Level file shipped with the game.
+  pub fn bundled_levels_path() -> std::path::PathBuf {
+    let mut path = std::path::PathBuf::from(get_resource_folder());
+    path.push("resources/levels.levelData");
+    path
+  }
+
   pub fn load_level(
     &mut self,
     level_ind: usize
-  ) {
-    let mut path = std::path::PathBuf::from(get_resource_folder());
-    path.push("resources/levels.levelData");
-    
-    let file_string = &std::fs::read_to_string(path).unwrap();
+  ) -> Result<(), String> {
+    self.load_level_from(&Level::bundled_levels_path(), level_ind)
+  }
 
-    let mut json_string = file_string.split("\n").skip(level_ind);
+  /// Loads level `level_ind` from a level file: one JSON level per line,
+  /// with blank lines skipped. Leaves this level as it was if the file can't
+  /// be read or has no valid level at that index.
+  pub fn load_level_from(
+    &mut self,
+    path: &std::path::Path,
+    level_ind: usize
+  ) -> Result<(), String> {
+    let file_string = &std::fs::read_to_string(path)
+      .map_err(|e| format!("Can't read level file {}: {}", path.display(), e))?;
+    let level_lines = || file_string.lines().filter(|line| !line.trim().is_empty());
+    let level_count = level_lines().count();
 
-    let new_level: Self = serde_json::from_str(json_string.next().unwrap()).unwrap();
+    let json_string = level_lines().nth(level_ind)
+      .ok_or_else(|| format!("No level {} in {}, it has {} levels", level_ind, path.display(), level_count))?;
+
+    let new_level: Self = serde_json::from_str(json_string)
+      .map_err(|e| format!("Level {} in {} is invalid: {}", level_ind, path.display(), e))?;
 
     self.attractors = new_level.attractors;
     self.zippers = new_level.zippers;
+    self.walls = new_level.walls;
+    self.collectibles = new_level.collectibles;
+    self.portals = new_level.portals;
+    self.hazards = new_level.hazards;
+    self.boundary = new_level.boundary;
+    self.checkpoints = new_level.checkpoints;
+    self.goal = new_level.goal;
+    self.number = level_ind;
+    self.source = Some(path.to_path_buf());
+    self.level_count = level_count;
+    self.rebuild_index();
+    Ok(())
+  }
+
+  pub fn has_next(&self) -> bool {
+    self.source.is_some() && self.number + 1 < self.level_count
+  }
+
+  /// Loads the level after this one from the same file. Does nothing on the
+  /// last level.
+  pub fn load_next(&mut self) -> Result<(), String> {
+    match (self.has_next(), self.source.clone()) {
+      (true, Some(path)) => self.load_level_from(&path, self.number + 1),
+      _ => Ok(()),
+    }
+  }
+
+  /// Must be called after objects are added, removed or moved.
+  pub fn rebuild_index(&mut self) {
+    self.index = SpatialIndex::build(self, DEFAULT_CELL_SIZE, SHIP_RADIUS);
+    self.arena = self.boundary.arena(self.bounds());
+  }
+
+  /// Moves every animated object to where its track puts it `millis` into
+  /// the run.
+  pub fn animate(
+    &mut self,
+    millis: u64,
+  ) {
+    self.millis = millis;
+    for attractor in self.attractors.iter_mut() {
+      attractor.animate(millis);
+    }
+    for zipper in self.zippers.iter_mut() {
+      zipper.animate(millis);
+    }
+    for wall in self.walls.iter_mut() {
+      wall.animate(millis);
+    }
+    for collectible in self.collectibles.iter_mut() {
+      collectible.animate(millis);
+    }
+  }
+
+  /// Puts every pickup back for a new attempt.
+  pub fn reset_collectibles(&mut self) {
+    for collectible in self.collectibles.iter_mut() {
+      collectible.set_collected(false);
+    }
+  }
+
+  /// Indices of the pickups collected so far.
+  pub fn collected(&self) -> Vec<usize> {
+    self.collectibles.iter().enumerate()
+      .filter(|(_, collectible)| collectible.is_collected())
+      .map(|(i, _)| i)
+      .collect()
+  }
+
+  /// Clears every checkpoint for a new attempt.
+  pub fn reset_checkpoints(&mut self) {
+    for checkpoint in self.checkpoints.iter_mut() {
+      checkpoint.set_passed(false);
+    }
+  }
+
+  /// Puts pickups and checkpoints back as they were when a respawn point
+  /// was recorded: `collected` pickups gone, the first `passed` gates done.
+  pub fn restore(
+    &mut self,
+    collected: &[usize],
+    passed: usize,
+  ) {
+    self.reset_collectibles();
+    for &i in collected {
+      if let Some(collectible) = self.collectibles.get_mut(i) {
+        collectible.set_collected(true);
+      }
+    }
+    for (i, checkpoint) in self.checkpoints.iter_mut().enumerate() {
+      checkpoint.set_passed(i < passed);
+    }
   }
 
-}
\ No newline at end of file
+  /// The gate the ship has to cross next, if any are left.
+  pub fn next_checkpoint(&self) -> Option<usize> {
+    self.checkpoints.iter().position(|checkpoint| !checkpoint.is_passed())
+  }
+
+  /// Marks the next gate passed if the ship crosses it along `motion`.
+  /// Gates further down the order are ignored.
+  pub fn pass_checkpoint_along(
+    &mut self,
+    motion: &Segment,
+  ) -> Option<usize> {
+    let line = DirectionalLine::new(&motion.start.into(), &motion.end.into());
+    let i = self.next_checkpoint()?;
+    let checkpoint = &mut self.checkpoints[i];
+
+    checkpoint.crossed_by(&line).map(|_| {
+      checkpoint.set_passed(true);
+      i
+    })
+  }
+
+  /// Marks every pickup the ship passes along `motion` as collected and
+  /// returns the points they were worth.
+  pub fn collect_along(
+    &mut self,
+    motion: &Segment,
+  ) -> u32 {
+    let mut points = 0;
+    for object in self.index.query_segment(motion) {
+      if let ObjectRef::Collectible(i) = object {
+        let collectible = &mut self.collectibles[i];
+        if !collectible.is_collected() && collectible.touched_by(motion, SHIP_RADIUS) {
+          collectible.set_collected(true);
+          points += collectible.value();
+        }
+      }
+    }
+    points
+  }
+
+  /// Box around every object's area of effect over its whole track; `None`
+  /// for an empty level.
+  pub fn bounds(&self) -> Option<Aabb> {
+    self.attractors.iter().map(|attractor| attractor.swept_bounds())
+      .chain(self.zippers.iter().map(|zipper| zipper.swept_bounds()))
+      .chain(self.walls.iter().filter_map(|wall| wall.swept_bounds()))
+      .chain(self.collectibles.iter().map(|collectible| collectible.swept_bounds()))
+      .chain(self.portals.iter().map(|portal| portal.bounds()))
+      .chain(self.hazards.iter().map(|hazard| hazard.bounds()))
+      .chain(self.checkpoints.iter().map(|checkpoint| checkpoint.bounds()))
+      .chain(self.goal.iter().map(|goal| goal.bounds()))
+      .reduce(|a, b| a.union(&b))
+  }
+
+  /// Attractors whose field might reach `point`.
+  pub fn attractors_near(
+    &self,
+    point: Vec2,
+  ) -> impl Iterator<Item = &Attractor> {
+    self.index.query_point(point).into_iter().filter_map(move |object| match object {
+      ObjectRef::Attractor(i) => self.attractors.get(i),
+      _ => None,
+    })
+  }
+
+  /// Zippers whose band might contain `point`.
+  pub fn zippers_near(
+    &self,
+    point: Vec2,
+  ) -> impl Iterator<Item = &Zipper> {
+    self.index.query_point(point).into_iter().filter_map(move |object| match object {
+      ObjectRef::Zipper(i) => self.zippers.get(i),
+      _ => None,
+    })
+  }
+
+}
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn blank_lines_do_not_count_as_levels() {
+    let path = std::env::temp_dir().join(format!("skyship_levels_{}.levelData", std::process::id()));
+    let json = r#"{"attractors":[],"zippers":[]}"#;
+    std::fs::write(&path, format!("{}\r\n\r\n{}\r\n\n", json, json)).unwrap();
+
+    let mut level = Level::default();
+    level.load_level_from(&path, 1).unwrap();
+    assert_eq!(level.level_count, 2);
+    assert!(!level.has_next());
+    assert!(level.load_level_from(&path, 2).is_err());
+
+    std::fs::remove_file(&path).unwrap();
+  }
+}
@@ -0,0 +1,239 @@
+use ggez::{graphics};
+use ggez::{Context, GameResult};
+
+use super::point::Point;
+use super::super::geometry::{Aabb, Segment, Vec2};
+use super::super::screen_context::ScreenContext;
+
+use serde::Deserialize;
+
+/// How far in front of (or behind) the exit mouth a teleported ship lands.
+const EXIT_CLEARANCE: f32 = 0.01;
+
+/// One mouth of a portal: a segment `width` long centred on `pos`, facing
+/// `facing` degrees. Ships enter by crossing it from the front.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PortalEnd {
+  pos: Point,
+  facing: f32,
+  width: f32,
+}
+
+impl PortalEnd {
+  pub fn new(
+    pos: &Point,
+    facing: f32,
+    width: f32,
+  ) -> PortalEnd {
+    PortalEnd {
+      pos: *pos,
+      facing,
+      width,
+    }
+  }
+
+  pub fn pos(&self) -> Point {
+    self.pos
+  }
+
+  pub fn width(&self) -> f32 {
+    self.width
+  }
+
+  /// Unit vector the front of the mouth faces.
+  pub fn normal(&self) -> Vec2 {
+    Vec2::from_angle(self.facing.to_radians())
+  }
+
+  pub fn segment(&self) -> Segment {
+    let half = self.normal().perp() * (self.width / 2.0);
+    let center: Vec2 = self.pos.into();
+    Segment::new(center - half, center + half)
+  }
+
+  /// Parameter along `motion` at which it crosses the mouth from front to
+  /// back. Leaving through the back, or starting exactly on the mouth, never
+  /// counts, which is what stops a ship bouncing straight back out.
+  pub fn entered_by(
+    &self,
+    motion: &Segment,
+  ) -> Option<f32> {
+    let center: Vec2 = self.pos.into();
+    let normal = self.normal();
+
+    if (motion.start - center).dot(normal) <= 0.0 || (motion.end - center).dot(normal) > 0.0 {
+      return None;
+    }
+
+    motion.line_intersect_params(&self.segment())
+      .filter(|(t, u)| (0.0..=1.0).contains(t) && (0.0..=1.0).contains(u))
+      .map(|(t, _)| t)
+  }
+}
+
+/// Two linked portal mouths; entering either one comes out of the other.
+/// By default velocity is turned with the portals, so the ship always
+/// leaves the exit head-on at the angle it went in; `keep_velocity` leaves
+/// it untouched instead.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Portal {
+  pub a: PortalEnd,
+  pub b: PortalEnd,
+  #[serde(default)]
+  pub keep_velocity: bool,
+}
+
+/// Where a ship comes out after passing through a portal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Teleport {
+  pub pos: Vec2,
+  pub vel: Vec2,
+}
+
+impl Portal {
+  pub fn new(
+    a: PortalEnd,
+    b: PortalEnd,
+  ) -> Portal {
+    Portal {
+      a,
+      b,
+      keep_velocity: false,
+    }
+  }
+
+  pub fn ends(&self) -> [&PortalEnd; 2] {
+    [&self.a, &self.b]
+  }
+
+  /// Earliest crossing into either mouth along `motion`, with where the
+  /// ship comes out.
+  pub fn traverse(
+    &self,
+    motion: &Segment,
+    vel: Vec2,
+  ) -> Option<(f32, Teleport)> {
+    [(&self.a, &self.b), (&self.b, &self.a)].iter()
+      .filter_map(|(entry, exit)| entry.entered_by(motion).map(|t| (t, self.exit(entry, exit, motion.point_at(t), vel))))
+      .fold(None, |earliest: Option<(f32, Teleport)>, hit| match earliest {
+        Some(e) if e.0 <= hit.0 => Some(e),
+        _ => Some(hit),
+      })
+  }
+
+  fn exit(
+    &self,
+    entry: &PortalEnd,
+    exit: &PortalEnd,
+    contact: Vec2,
+    vel: Vec2,
+  ) -> Teleport {
+    // Turns the entry's frame onto the exit's, with "into the entry"
+    // becoming "out of the exit".
+    let turn = (exit.facing - entry.facing).to_radians() + std::f32::consts::PI;
+
+    let along = (contact - Vec2::from(entry.pos)) * (exit.width / entry.width);
+    let vel = if self.keep_velocity {vel} else {vel.rotate(turn)};
+
+    // Land on whichever side the ship is heading towards, so its next move
+    // can't cross the exit mouth from the front.
+    let side = if vel.dot(exit.normal()) >= 0.0 {1.0} else {-1.0};
+
+    Teleport {
+      pos: Vec2::from(exit.pos) + along.rotate(turn) + exit.normal() * (side * EXIT_CLEARANCE),
+      vel,
+    }
+  }
+
+  pub fn bounds(&self) -> Aabb {
+    let a = self.a.segment();
+    let b = self.b.segment();
+    Aabb::from_points(a.start, a.end).union(&Aabb::from_points(b.start, b.end))
+  }
+
+  pub fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    let mut mesh = graphics::MeshBuilder::new();
+
+    for (end, color) in [(&self.a, [0.2, 0.6, 1.0]), (&self.b, [1.0, 0.6, 0.1])].iter() {
+      let segment = end.segment();
+      let mouth = [
+        screen.point_game_to_screen(segment.start.into()),
+        screen.point_game_to_screen(segment.end.into()),
+      ];
+      mesh.line(&mouth, screen.size_game_to_screen(6.0), [color[0], color[1], color[2], 1.0].into())?;
+
+      // A short glow on the front side shows which way the mouth faces.
+      let glow = end.normal() * 12.0;
+      let front = [
+        screen.point_game_to_screen((segment.start + glow).into()),
+        screen.point_game_to_screen((segment.end + glow).into()),
+      ];
+      mesh.line(&front, screen.size_game_to_screen(3.0), [color[0], color[1], color[2], 0.35].into())?;
+    }
+
+    let mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn pair() -> Portal {
+    // Mouth a faces left at the origin; mouth b faces up at (500, 0).
+    Portal::new(
+      PortalEnd::new(&Point {x: 0.0, y: 0.0}, 180.0, 100.0),
+      PortalEnd::new(&Point {x: 500.0, y: 0.0}, -90.0, 100.0),
+    )
+  }
+
+  #[test]
+  fn entering_from_the_front_only() {
+    let portal = pair();
+
+    let forward = Segment::new(Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0));
+    assert!(portal.a.entered_by(&forward).is_some());
+
+    let backward = Segment::new(Vec2::new(10.0, 0.0), Vec2::new(-10.0, 0.0));
+    assert!(portal.a.entered_by(&backward).is_none());
+
+    let past_the_edge = Segment::new(Vec2::new(-10.0, 80.0), Vec2::new(10.0, 80.0));
+    assert!(portal.a.entered_by(&past_the_edge).is_none());
+  }
+
+  #[test]
+  fn velocity_turns_with_the_portals() {
+    let portal = pair();
+    let motion = Segment::new(Vec2::new(-10.0, 20.0), Vec2::new(10.0, 20.0));
+
+    let (t, out) = portal.traverse(&motion, Vec2::new(0.3, 0.0)).unwrap();
+    assert!((t - 0.5).abs() < 1e-4);
+    assert!(out.vel.approx_eq(Vec2::new(0.0, -0.3), 1e-5));
+    assert!(out.pos.approx_eq(Vec2::new(520.0, -EXIT_CLEARANCE), 1e-3));
+  }
+
+  #[test]
+  fn keep_velocity_leaves_it_alone() {
+    let portal = Portal {keep_velocity: true, ..pair()};
+    let motion = Segment::new(Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0));
+
+    let (_, out) = portal.traverse(&motion, Vec2::new(0.3, 0.0)).unwrap();
+    assert_eq!(out.vel, Vec2::new(0.3, 0.0));
+  }
+
+  #[test]
+  fn exit_does_not_immediately_re_enter() {
+    for portal in [pair(), Portal {keep_velocity: true, ..pair()}] {
+      let motion = Segment::new(Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0));
+      let (_, out) = portal.traverse(&motion, Vec2::new(0.3, 0.0)).unwrap();
+
+      let next = Segment::new(out.pos, out.pos + out.vel * 16.0);
+      assert!(portal.traverse(&next, out.vel).is_none());
+    }
+  }
+}
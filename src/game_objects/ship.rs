@@ -0,0 +1,731 @@
+use ggez::{graphics};
+use ggez::{Context, GameResult};
+
+use super::level::Level;
+use super::spatial_index::ObjectRef;
+use super::wall::WallKind;
+use super::portal::Teleport;
+use super::boundary::{self, BoundaryMode};
+use super::super::geometry::{Segment, Vec2};
+use super::super::screen_context::ScreenContext;
+
+/// Radius used when sweeping the ship against attractor bodies.
+pub const SHIP_RADIUS: f32 = 8.0;
+/// Launch speed, in game units per millisecond.
+pub const SHIP_SPEED: f32 = 0.3;
+/// Acceleration a zipper of strength 1 applies, in units per ms².
+pub const ZIPPER_ACCEL: f32 = 0.0005;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orbit {
+  pub attractor: usize,
+  pub radius: f32,
+  pub angle: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ship {
+  pub pos: Vec2,
+  pub vel: Vec2,
+  pub orbit: Option<Orbit>,
+}
+
+/// What happened during one `Ship::step`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StepEvents {
+  pub crashed: Option<Vec2>,
+  pub captured: Option<usize>,
+  pub released: bool,
+  pub bounced: Option<usize>,
+  pub teleported: Option<usize>,
+  /// Where the ship went into the portal it teleported through, or off the
+  /// arena edge it wrapped across. Its flight this step covered the ground
+  /// up to here and none after.
+  pub left_at: Option<Vec2>,
+  pub wrapped: bool,
+  /// A zipper pushing the ship this step.
+  pub boosted: Option<usize>,
+}
+
+struct WallHit {
+  wall: usize,
+  t: f32,
+  normal: Vec2,
+}
+
+impl Ship {
+  /// Starts the ship orbiting the level's first orbit attractor, halfway
+  /// between its body and the edge of its field.
+  pub fn spawn(level: &Level) -> Ship {
+    match level.attractors.iter().enumerate().find(|(_, attractor)| attractor.captures()) {
+      Some((i, attractor)) => {
+        let radius = (attractor.field_size() + attractor.phys_size()) / 2.0;
+        let orbit = Orbit {attractor: i, radius, angle: -std::f32::consts::FRAC_PI_2};
+        let mut ship = Ship {pos: Vec2::ZERO, vel: Vec2::ZERO, orbit: Some(orbit)};
+        ship.place_on_orbit(level, SHIP_SPEED);
+        ship
+      }
+      None => Ship {
+        pos: Vec2::ZERO,
+        vel: Vec2::new(SHIP_SPEED, 0.0),
+        orbit: None,
+      },
+    }
+  }
+
+  pub fn speed(&self) -> f32 {
+    self.vel.length()
+  }
+
+  /// Advances the ship by `dt` milliseconds. `grab` toggles the orbit: it
+  /// releases an orbiting ship, or latches onto any field the ship touched
+  /// during this step.
+  pub fn step(
+    &mut self,
+    level: &Level,
+    grab: bool,
+    dt: f32,
+  ) -> StepEvents {
+    let start = self.pos;
+    let mut events = self.advance(level, grab, dt);
+
+    if events.crashed.is_some() {
+      return events;
+    }
+
+    if let Some(arena) = level.arena {
+      if !arena.contains(self.pos) {
+        match level.boundary.mode {
+          BoundaryMode::Kill => {
+            self.pos = Vec2::new(
+              self.pos.x.clamp(arena.min.x, arena.max.x),
+              self.pos.y.clamp(arena.min.y, arena.max.y),
+            );
+            events.crashed = Some(self.pos);
+          }
+          BoundaryMode::Wrap => {
+            events.left_at = Some(boundary::exit_point(&arena, &Segment::new(start, self.pos)));
+            self.pos = boundary::wrap(&arena, self.pos);
+            self.orbit = None;
+            events.wrapped = true;
+          }
+        }
+      }
+    }
+
+    events
+  }
+
+  /// Where the ship would go over the next `ticks` steps of `dt` if it let
+  /// go of its orbit now, ending early at a crash, teleport or wrap. Runs
+  /// the same `step` as play, so it leans on the level's broad-phase and
+  /// treats moving objects as frozen where they are.
+  pub fn predict_release(
+    &self,
+    level: &Level,
+    ticks: usize,
+    dt: f32,
+  ) -> Vec<Vec2> {
+    let mut ghost = self.clone();
+    let mut path = vec![ghost.pos];
+
+    for i in 0..ticks {
+      let events = ghost.step(level, i == 0 && ghost.orbit.is_some(), dt);
+      path.push(ghost.pos);
+
+      if events.crashed.is_some() || events.teleported.is_some() || events.wrapped {
+        break;
+      }
+    }
+
+    path
+  }
+
+  fn advance(
+    &mut self,
+    level: &Level,
+    grab: bool,
+    dt: f32,
+  ) -> StepEvents {
+    let mut events = StepEvents::default();
+
+    let mut grab = grab;
+    if grab && self.orbit.is_some() {
+      self.orbit = None;
+      events.released = true;
+      grab = false;
+    }
+
+    if self.orbit.is_some() {
+      let before = self.pos;
+      self.step_orbit(level, dt);
+
+      // Hazards cutting through an orbit destroy the ship, walls knock it
+      // out of the orbit and portals carry it off.
+      let chord = Segment::new(before, self.pos);
+      let candidates = level.index.query_segment(&chord);
+      let crash = earliest_crash(level, &chord, &candidates);
+      let wall_hit = first_wall_hit(level, &chord, &candidates);
+
+      if let Some((i, t, teleport)) = first_portal(level, &chord, self.vel, &candidates) {
+        let blocked = crash.is_some_and(|c| c < t) || wall_hit.as_ref().is_some_and(|hit| hit.t < t);
+        if !blocked {
+          self.orbit = None;
+          self.teleport(&chord, i, t, teleport, &mut events);
+          return events;
+        }
+      }
+
+      if let Some(t) = crash {
+        self.orbit = None;
+        self.pos = chord.point_at(t);
+        events.crashed = Some(self.pos);
+      } else if let Some(hit) = wall_hit {
+        self.orbit = None;
+        self.hit_wall(level, &chord, hit, &mut events);
+      }
+      return events;
+    }
+
+    let start = self.pos;
+    let motion = Segment::new(start, start + self.vel * dt);
+    let candidates = level.index.query_segment(&motion);
+
+    let crash = earliest_crash(level, &motion, &candidates);
+    let wall_hit = first_wall_hit(level, &motion, &candidates);
+
+    // The rest of the step is skipped after a teleport, so the ship can't
+    // dive straight back into either mouth.
+    if let Some((i, t, teleport)) = first_portal(level, &motion, self.vel, &candidates) {
+      let blocked = crash.is_some_and(|c| c < t) || wall_hit.as_ref().is_some_and(|hit| hit.t < t);
+      if !blocked {
+        self.teleport(&motion, i, t, teleport, &mut events);
+        return events;
+      }
+    }
+
+    if let Some(hit) = wall_hit {
+      let crash_first = crash.is_some_and(|t| t < hit.t);
+      if !crash_first {
+        self.hit_wall(level, &motion, hit, &mut events);
+        return events;
+      }
+    }
+
+    if let Some(t) = crash {
+      self.pos = motion.point_at(t);
+      events.crashed = Some(self.pos);
+      return events;
+    }
+
+    if grab {
+      let touched = candidates.iter().filter_map(|object| match *object {
+        ObjectRef::Attractor(i) => level.attractors.get(i)
+          .filter(|attractor| attractor.captures())
+          .and_then(|attractor| motion.sweep_circle(attractor.pos().into(), attractor.field_size()))
+          .map(|t| (i, t)),
+        _ => None,
+      }).fold(None, |earliest: Option<(usize, f32)>, hit| match earliest {
+        Some(e) if e.1 <= hit.1 => Some(e),
+        _ => Some(hit),
+      });
+
+      if let Some((i, t)) = touched {
+        self.pos = motion.point_at(t);
+        self.capture(level, i);
+        events.captured = Some(i);
+        self.step_orbit(level, dt * (1.0 - t));
+        return events;
+      }
+    }
+
+    // Zippers push for the part of the step spent inside their band; wells
+    // and repulsors act from where the step starts.
+    let mut accel = Vec2::ZERO;
+    for object in candidates.iter() {
+      if let ObjectRef::Attractor(i) = *object {
+        accel += level.attractors[i].accel_at(start);
+      }
+      if let ObjectRef::Zipper(i) = *object {
+        let zipper = &level.zippers[i];
+        if let Some((enter, exit)) = zipper.band_interval(&motion) {
+          let direction = zipper.segment().direction().normalize();
+          accel += direction * (zipper.strength * ZIPPER_ACCEL * (exit - enter));
+          events.boosted = Some(i);
+        }
+      }
+    }
+
+    self.vel += accel * dt;
+    self.pos = motion.end + accel * (0.5 * dt * dt);
+
+    events
+  }
+
+  fn teleport(
+    &mut self,
+    motion: &Segment,
+    portal: usize,
+    t: f32,
+    teleport: Teleport,
+    events: &mut StepEvents,
+  ) {
+    events.left_at = Some(motion.point_at(t));
+    events.teleported = Some(portal);
+    self.pos = teleport.pos;
+    self.vel = teleport.vel;
+  }
+
+  fn hit_wall(
+    &mut self,
+    level: &Level,
+    motion: &Segment,
+    hit: WallHit,
+    events: &mut StepEvents,
+  ) {
+    let wall = &level.walls[hit.wall];
+    let contact = motion.point_at(hit.t);
+
+    if wall.kind() == WallKind::Deadly {
+      self.pos = contact;
+      events.crashed = Some(contact);
+    } else {
+      self.pos = contact + hit.normal * 0.01;
+      self.vel = wall.reflect(motion.direction().try_normalize().map_or(self.vel, |d| d * self.speed()), hit.normal);
+      events.bounced = Some(hit.wall);
+    }
+  }
+
+  fn capture(
+    &mut self,
+    level: &Level,
+    attractor: usize,
+  ) {
+    let center: Vec2 = level.attractors[attractor].pos().into();
+    let offset = self.pos - center;
+    self.orbit = Some(Orbit {
+      attractor,
+      radius: offset.length(),
+      angle: offset.angle(),
+    });
+  }
+
+  fn step_orbit(
+    &mut self,
+    level: &Level,
+    dt: f32,
+  ) {
+    let speed = self.speed().max(SHIP_SPEED);
+
+    if let Some(orbit) = self.orbit.as_mut() {
+      let spin = level.attractors[orbit.attractor].spin_mult();
+      orbit.angle += spin * speed * dt / orbit.radius;
+    }
+
+    self.place_on_orbit(level, speed);
+  }
+
+  fn place_on_orbit(
+    &mut self,
+    level: &Level,
+    speed: f32,
+  ) {
+    if let Some(orbit) = self.orbit {
+      let attractor = &level.attractors[orbit.attractor];
+      let center: Vec2 = attractor.pos().into();
+      let radial = Vec2::from_angle(orbit.angle);
+
+      self.pos = center + radial * orbit.radius;
+      self.vel = radial.perp() * (speed * attractor.spin_mult().signum());
+    }
+  }
+
+  pub fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    let heading = self.vel.try_normalize().unwrap_or(Vec2::new(1.0, 0.0));
+    let size = SHIP_RADIUS * 1.5;
+
+    let points: Vec<mint::Point2<f32>> = [
+      self.pos + heading * size,
+      self.pos + heading.rotate(2.5) * size,
+      self.pos - heading * (size * 0.4),
+      self.pos + heading.rotate(-2.5) * size,
+    ].iter().map(|point| screen.point_game_to_screen((*point).into())).collect();
+
+    let mesh = graphics::Mesh::new_polygon(
+      ctx,
+      graphics::DrawMode::fill(),
+      &points,
+      [1.0, 1.0, 1.0, 1.0].into()
+    )?;
+
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+}
+
+/// Earliest point along `motion` at which the ship touches an attractor's
+/// body or a hazard, even if both ends of the step lie outside it.
+fn earliest_crash(
+  level: &Level,
+  motion: &Segment,
+  candidates: &[ObjectRef],
+) -> Option<f32> {
+  candidates.iter().filter_map(|object| match *object {
+    ObjectRef::Attractor(i) => level.attractors.get(i)
+      .and_then(|attractor| motion.sweep_circle(attractor.pos().into(), attractor.phys_size() + SHIP_RADIUS)),
+    ObjectRef::Hazard(i) => level.hazards.get(i)
+      .and_then(|hazard| hazard.hit_along(motion, SHIP_RADIUS)),
+    _ => None,
+  }).fold(None, |earliest: Option<f32>, t| Some(earliest.map_or(t, |e| e.min(t))))
+}
+
+fn first_portal(
+  level: &Level,
+  motion: &Segment,
+  vel: Vec2,
+  candidates: &[ObjectRef],
+) -> Option<(usize, f32, Teleport)> {
+  candidates.iter().filter_map(|object| match *object {
+    ObjectRef::Portal(i) => level.portals.get(i)
+      .and_then(|portal| portal.traverse(motion, vel))
+      .map(|(t, teleport)| (i, t, teleport)),
+    _ => None,
+  }).fold(None, |earliest: Option<(usize, f32, Teleport)>, hit| match earliest {
+    Some(e) if e.1 <= hit.1 => Some(e),
+    _ => Some(hit),
+  })
+}
+
+fn first_wall_hit(
+  level: &Level,
+  motion: &Segment,
+  candidates: &[ObjectRef],
+) -> Option<WallHit> {
+  let direction = motion.direction();
+  let mut first: Option<WallHit> = None;
+
+  for object in candidates.iter() {
+    let i = match *object {
+      ObjectRef::Wall(i) => i,
+      _ => continue,
+    };
+    let wall = &level.walls[i];
+    let radius = wall.thickness() / 2.0 + SHIP_RADIUS;
+
+    for segment in wall.segments() {
+      let t = match motion.sweep_capsule(&segment, radius) {
+        Some(t) => t,
+        None => continue,
+      };
+
+      let contact = motion.point_at(t);
+      let normal = (contact - segment.closest_point(contact)).try_normalize()
+        .unwrap_or_else(|| -direction.normalize());
+
+      // Already touching but moving away, e.g. right after a bounce.
+      if direction.dot(normal) >= 0.0 {
+        continue;
+      }
+
+      if first.as_ref().is_none_or(|hit| t < hit.t) {
+        first = Some(WallHit {wall: i, t, normal});
+      }
+    }
+  }
+
+  first
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::attractor::{Attractor, AttractorKind};
+  use super::super::point::Point;
+  use super::super::zipper::Zipper;
+  use super::super::wall::Wall;
+
+  fn level_with(attractors: Vec<Attractor>, zippers: Vec<Zipper>) -> Level {
+    level_with_walls(attractors, zippers, vec![])
+  }
+
+  fn level_with_walls(attractors: Vec<Attractor>, zippers: Vec<Zipper>, walls: Vec<Wall>) -> Level {
+    let mut level = Level {attractors, zippers, walls, ..Level::default()};
+    level.rebuild_index();
+    level
+  }
+
+  fn free(pos: Vec2, vel: Vec2) -> Ship {
+    Ship {pos, vel, orbit: None}
+  }
+
+  #[test]
+  fn fast_ship_cannot_tunnel_through_attractor() {
+    let level = level_with(vec![Attractor::new(&Point {x: 0.0, y: 0.0}, 150.0, 50.0, true)], vec![]);
+    // Covers 2000 units in one step, jumping clean over the body.
+    let mut ship = free(Vec2::new(-1000.0, 0.0), Vec2::new(125.0, 0.0));
+
+    let events = ship.step(&level, false, 16.0);
+    let crash = events.crashed.expect("swept test should catch the pass-through");
+    assert!((crash.x - -(50.0 + SHIP_RADIUS)).abs() < 1e-2);
+  }
+
+  #[test]
+  fn grab_catches_field_crossed_mid_step() {
+    let level = level_with(vec![Attractor::new(&Point {x: 0.0, y: 0.0}, 150.0, 50.0, true)], vec![]);
+    // Passes 100 units from the centre, through the field but clear of the body.
+    let mut ship = free(Vec2::new(-1000.0, 100.0), Vec2::new(125.0, 0.0));
+
+    let events = ship.step(&level, true, 16.0);
+    assert_eq!(events.crashed, None);
+    assert_eq!(events.captured, Some(0));
+    assert!(ship.orbit.is_some());
+  }
+
+  #[test]
+  fn orbit_keeps_radius_and_release_flies_tangent() {
+    let level = level_with(vec![Attractor::new(&Point {x: 0.0, y: 0.0}, 150.0, 50.0, true)], vec![]);
+    let mut ship = Ship::spawn(&level);
+
+    for _ in 0..100 {
+      ship.step(&level, false, 16.0);
+      assert!((ship.pos.length() - 100.0).abs() < 1e-2);
+    }
+
+    let events = ship.step(&level, true, 16.0);
+    assert!(events.released);
+    assert!(ship.orbit.is_none());
+    assert!(ship.pos.dot(ship.vel).abs() < 1e-1 * ship.pos.length());
+  }
+
+  #[test]
+  fn gravity_well_bends_path_without_capture() {
+    let well = Attractor::new(&Point {x: 0.0, y: 0.0}, 150.0, 50.0, true).with_kind(AttractorKind::GravityWell);
+    let level = level_with(vec![well], vec![]);
+    let mut ship = free(Vec2::new(-200.0, 100.0), Vec2::new(SHIP_SPEED, 0.0));
+
+    for _ in 0..80 {
+      let events = ship.step(&level, true, 16.0);
+      assert_eq!(events.captured, None);
+      assert_eq!(events.crashed, None);
+    }
+
+    assert!(ship.orbit.is_none());
+    assert!(ship.vel.y < 0.0);
+  }
+
+  #[test]
+  fn repulsor_pushes_ship_away() {
+    let repulsor = Attractor::new(&Point {x: 0.0, y: 0.0}, 150.0, 50.0, true).with_kind(AttractorKind::Repulsor);
+    let level = level_with(vec![repulsor], vec![]);
+    let mut ship = free(Vec2::new(-140.0, 0.0), Vec2::new(0.05, 0.0));
+
+    for _ in 0..40 {
+      assert_eq!(ship.step(&level, false, 16.0).crashed, None);
+    }
+
+    assert!(ship.vel.x < 0.0);
+  }
+
+  #[test]
+  fn spawn_skips_attractors_that_cannot_capture() {
+    let level = level_with(vec![
+      Attractor::new(&Point {x: 0.0, y: 0.0}, 150.0, 50.0, true).with_kind(AttractorKind::Repulsor),
+      Attractor::new(&Point {x: 1000.0, y: 0.0}, 150.0, 50.0, true),
+    ], vec![]);
+
+    assert_eq!(Ship::spawn(&level).orbit.map(|orbit| orbit.attractor), Some(1));
+  }
+
+  #[test]
+  fn portal_carries_ship_to_the_other_end() {
+    use super::super::portal::{Portal, PortalEnd};
+
+    let mut level = level_with(vec![], vec![]);
+    level.portals.push(Portal::new(
+      PortalEnd::new(&Point {x: 100.0, y: 0.0}, 180.0, 80.0),
+      PortalEnd::new(&Point {x: 2000.0, y: 2000.0}, 90.0, 80.0),
+    ));
+    level.rebuild_index();
+
+    let mut ship = free(Vec2::new(0.0, 0.0), Vec2::new(SHIP_SPEED, 0.0));
+    let mut teleports = 0;
+    for _ in 0..60 {
+      if ship.step(&level, false, 16.0).teleported.is_some() {
+        teleports += 1;
+      }
+    }
+
+    assert_eq!(teleports, 1);
+    assert!(ship.pos.y > 2000.0);
+    assert!(ship.vel.approx_eq(Vec2::new(0.0, SHIP_SPEED), 1e-5));
+  }
+
+  #[test]
+  fn portal_on_an_orbit_carries_ship_off_it() {
+    use super::super::portal::{Portal, PortalEnd};
+
+    // The spawn orbit runs clockwise from straight up, crossing (100, 0)
+    // heading down.
+    let mut level = level_with(vec![Attractor::new(&Point {x: 0.0, y: 0.0}, 150.0, 50.0, true)], vec![]);
+    level.portals.push(Portal::new(
+      PortalEnd::new(&Point {x: 100.0, y: 0.0}, 270.0, 80.0),
+      PortalEnd::new(&Point {x: 2000.0, y: 2000.0}, 90.0, 80.0),
+    ));
+    level.rebuild_index();
+
+    let mut ship = Ship::spawn(&level);
+    let events = (0..300).map(|_| ship.step(&level, false, 16.0)).find(|events| events.teleported.is_some())
+      .expect("orbit should pass through the portal");
+
+    assert!(ship.orbit.is_none());
+    assert!(ship.pos.y > 2000.0);
+    assert!(events.left_at.unwrap().approx_eq(Vec2::new(100.0, 0.0), 1.0));
+  }
+
+  #[test]
+  fn hazard_destroys_ship() {
+    use super::super::hazard::Hazard;
+
+    let mut level = level_with(vec![], vec![]);
+    level.hazards.push(Hazard::Circle {center: Point {x: 200.0, y: 0.0}, radius: 50.0});
+    level.rebuild_index();
+
+    let mut ship = free(Vec2::new(0.0, 0.0), Vec2::new(SHIP_SPEED, 0.0));
+    let crash = (0..60).find_map(|_| ship.step(&level, false, 16.0).crashed).unwrap();
+    assert!((crash.x - (150.0 - SHIP_RADIUS)).abs() < 1e-2);
+  }
+
+  #[test]
+  fn boundary_kills_or_wraps() {
+    use super::super::boundary::Boundary;
+
+    let mut level = level_with(vec![], vec![]);
+    level.boundary = Boundary {
+      min: Some(Point {x: -100.0, y: -100.0}),
+      max: Some(Point {x: 100.0, y: 100.0}),
+      mode: BoundaryMode::Kill,
+    };
+    level.rebuild_index();
+
+    let mut ship = free(Vec2::new(0.0, 0.0), Vec2::new(SHIP_SPEED, 0.0));
+    let crash = (0..60).find_map(|_| ship.step(&level, false, 16.0).crashed).unwrap();
+    assert_eq!(crash.x, 100.0);
+
+    level.boundary.mode = BoundaryMode::Wrap;
+    let mut ship = free(Vec2::new(0.0, 0.0), Vec2::new(SHIP_SPEED, 0.0));
+    let mut wrapped = false;
+    for _ in 0..30 {
+      let events = ship.step(&level, false, 16.0);
+      assert_eq!(events.crashed, None);
+      if events.wrapped {
+        assert_eq!(events.left_at.map(|exit| exit.x), Some(100.0));
+      }
+      wrapped |= events.wrapped;
+    }
+    assert!(wrapped);
+    assert!(ship.pos.x < 0.0);
+  }
+
+  #[test]
+  fn predicted_release_matches_actual_release() {
+    let level = level_with(
+      vec![Attractor::new(&Point {x: 0.0, y: 0.0}, 150.0, 50.0, true)],
+      vec![Zipper::new(&Point {x: -900.0, y: -75.0}, &Point {x: -200.0, y: -75.0}, 100.0, 100.0, 1.0)]
+    );
+    let mut ship = Ship::spawn(&level);
+    for _ in 0..20 {
+      ship.step(&level, false, 16.0);
+    }
+
+    let predicted = ship.predict_release(&level, 50, 16.0);
+    assert!(ship.orbit.is_some());
+    assert_eq!(predicted.len(), 51);
+
+    ship.step(&level, true, 16.0);
+    for _ in 1..50 {
+      ship.step(&level, false, 16.0);
+    }
+    assert_eq!(predicted.last(), Some(&ship.pos));
+  }
+
+  #[test]
+  fn zipper_accelerates_along_its_direction() {
+    let level = level_with(vec![], vec![Zipper::new(&Point {x: 0.0, y: 0.0}, &Point {x: 1000.0, y: 0.0}, 100.0, 100.0, 1.0)]);
+    let mut ship = free(Vec2::new(100.0, 10.0), Vec2::new(0.0, SHIP_SPEED));
+
+    ship.step(&level, false, 16.0);
+    assert!(ship.vel.x > 0.0);
+  }
+
+  #[test]
+  fn zipper_pushes_a_ship_crossing_it_in_one_step() {
+    let level = level_with(vec![], vec![Zipper::new(&Point {x: 0.0, y: 0.0}, &Point {x: 1000.0, y: 0.0}, 5.0, 100.0, 1.0)]);
+    let mut ship = free(Vec2::new(500.0, -20.0), Vec2::new(0.0, 2.5));
+
+    let events = ship.step(&level, false, 16.0);
+    assert!(ship.pos.y > 5.0);
+    assert_eq!(events.boosted, Some(0));
+    assert!(ship.vel.x > 0.0);
+  }
+
+  fn floor(kind: WallKind) -> Wall {
+    Wall::new(vec![Point {x: -1000.0, y: 100.0}, Point {x: 1000.0, y: 100.0}], 10.0, kind)
+  }
+
+  #[test]
+  fn bouncy_wall_reflects_velocity() {
+    let level = level_with_walls(vec![], vec![], vec![floor(WallKind::Bouncy)]);
+    let mut ship = free(Vec2::new(0.0, 0.0), Vec2::new(0.3, 0.3));
+
+    let mut bounced = false;
+    for _ in 0..40 {
+      bounced |= ship.step(&level, false, 16.0).bounced.is_some();
+    }
+
+    assert!(bounced);
+    assert!(ship.vel.y < 0.0);
+    assert!((ship.vel.y + 0.3).abs() < 1e-4);
+    assert!(ship.pos.y < 100.0 - 5.0 - SHIP_RADIUS + 0.1);
+  }
+
+  #[test]
+  fn fast_ship_cannot_tunnel_through_wall() {
+    let level = level_with_walls(vec![], vec![], vec![floor(WallKind::Solid)]);
+    let mut ship = free(Vec2::new(0.0, 0.0), Vec2::new(0.0, 50.0));
+
+    let events = ship.step(&level, false, 16.0);
+    assert_eq!(events.bounced, Some(0));
+    assert!(ship.pos.y < 100.0);
+    assert!(ship.vel.y < 0.0);
+  }
+
+  #[test]
+  fn deadly_wall_crashes() {
+    let level = level_with_walls(vec![], vec![], vec![floor(WallKind::Deadly)]);
+    let mut ship = free(Vec2::new(0.0, 0.0), Vec2::new(0.0, 50.0));
+
+    let crash = ship.step(&level, false, 16.0).crashed.unwrap();
+    assert!((crash.y - (100.0 - 5.0 - SHIP_RADIUS)).abs() < 1e-2);
+  }
+
+  #[test]
+  fn step_is_deterministic() {
+    let level = level_with(
+      vec![Attractor::new(&Point {x: 0.0, y: 0.0}, 150.0, 50.0, true)],
+      vec![Zipper::new(&Point {x: -900.0, y: -75.0}, &Point {x: -200.0, y: -75.0}, 100.0, 100.0, 1.0)]
+    );
+
+    let run = || {
+      let mut ship = Ship::spawn(&level);
+      for i in 0..300 {
+        ship.step(&level, i == 40, 16.0);
+      }
+      ship
+    };
+
+    assert_eq!(run(), run());
+  }
+}
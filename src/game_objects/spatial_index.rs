@@ -0,0 +1,360 @@
+use super::level::Level;
+use super::animation::Animated;
+use super::super::geometry::{Aabb, Segment, Vec2};
+
+use std::collections::HashMap;
+
+pub const DEFAULT_CELL_SIZE: f32 = 256.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ObjectRef {
+  Attractor(usize),
+  Zipper(usize),
+  Wall(usize),
+  Collectible(usize),
+  Portal(usize),
+  Hazard(usize),
+}
+
+/// Uniform grid over the level's objects. Each cell lists every object whose
+/// area of effect touches it, so queries only return candidates; callers
+/// still run the exact test (`in_range`, `point_in_range`, ...).
+pub struct SpatialIndex {
+  cell_size: f32,
+  cells: HashMap<(i32, i32), Vec<ObjectRef>>,
+}
+
+impl SpatialIndex {
+  pub fn new(cell_size: f32) -> SpatialIndex {
+    SpatialIndex {
+      cell_size,
+      cells: HashMap::new(),
+    }
+  }
+
+  /// Objects the mover collides with (walls, pickups, portals, hazards) are
+  /// inflated by `mover_radius` so a query on the mover's centre finds them.
+  pub fn build(
+    level: &Level,
+    cell_size: f32,
+    mover_radius: f32,
+  ) -> SpatialIndex {
+    let mut index = SpatialIndex::new(cell_size);
+
+    // Moving objects are filed under every cell their track can reach, so
+    // the index never needs rebuilding while they animate.
+    for (i, attractor) in level.attractors.iter().enumerate() {
+      index.insert_aabb(ObjectRef::Attractor(i), &attractor.swept_bounds());
+    }
+
+    for (i, zipper) in level.zippers.iter().enumerate() {
+      if zipper.track().is_some() {
+        index.insert_aabb(ObjectRef::Zipper(i), &zipper.swept_bounds());
+      } else {
+        index.insert_segment(ObjectRef::Zipper(i), &zipper.segment(), zipper.width());
+      }
+    }
+
+    for (i, wall) in level.walls.iter().enumerate() {
+      if wall.track().is_some() {
+        if let Some(bounds) = wall.swept_bounds() {
+          index.insert_aabb(ObjectRef::Wall(i), &bounds.expand(mover_radius));
+        }
+      } else {
+        for segment in wall.segments() {
+          index.insert_segment(ObjectRef::Wall(i), &segment, wall.thickness() / 2.0 + mover_radius);
+        }
+      }
+    }
+
+    for (i, collectible) in level.collectibles.iter().enumerate() {
+      index.insert_aabb(ObjectRef::Collectible(i), &collectible.swept_bounds().expand(mover_radius));
+    }
+
+    for (i, portal) in level.portals.iter().enumerate() {
+      for end in portal.ends() {
+        index.insert_segment(ObjectRef::Portal(i), &end.segment(), mover_radius);
+      }
+    }
+
+    for (i, hazard) in level.hazards.iter().enumerate() {
+      index.insert_aabb(ObjectRef::Hazard(i), &hazard.bounds().expand(mover_radius));
+    }
+
+    index
+  }
+
+  pub fn cell_size(&self) -> f32 {
+    self.cell_size
+  }
+
+  pub fn cell_of(&self, point: Vec2) -> (i32, i32) {
+    (
+      (point.x / self.cell_size).floor() as i32,
+      (point.y / self.cell_size).floor() as i32,
+    )
+  }
+
+  pub fn cell_bounds(&self, cell: (i32, i32)) -> Aabb {
+    let min = Vec2::new(cell.0 as f32 * self.cell_size, cell.1 as f32 * self.cell_size);
+    Aabb::new(min, min + Vec2::new(self.cell_size, self.cell_size))
+  }
+
+  /// Cells holding at least one object.
+  pub fn occupied_cells(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+    self.cells.keys().copied()
+  }
+
+  pub fn insert_aabb(
+    &mut self,
+    object: ObjectRef,
+    bounds: &Aabb,
+  ) {
+    let (min_x, min_y) = self.cell_of(bounds.min);
+    let (max_x, max_y) = self.cell_of(bounds.max);
+
+    for x in min_x..=max_x {
+      for y in min_y..=max_y {
+        self.insert_into_cell((x, y), object);
+      }
+    }
+  }
+
+  /// Walls insert once per segment, so skip cells that already list them.
+  fn insert_into_cell(
+    &mut self,
+    cell: (i32, i32),
+    object: ObjectRef,
+  ) {
+    let objects = self.cells.entry(cell).or_default();
+    if !objects.contains(&object) {
+      objects.push(object);
+    }
+  }
+
+  /// Inserts into the cells within `radius` of the segment, rather than its
+  /// whole bounding box, so long diagonal zippers stay cheap.
+  pub fn insert_segment(
+    &mut self,
+    object: ObjectRef,
+    segment: &Segment,
+    radius: f32,
+  ) {
+    let bounds = Aabb::from_points(segment.start, segment.end).expand(radius);
+    let (min_x, min_y) = self.cell_of(bounds.min);
+    let (max_x, max_y) = self.cell_of(bounds.max);
+
+    let half_diagonal = self.cell_size * std::f32::consts::FRAC_1_SQRT_2;
+
+    for x in min_x..=max_x {
+      for y in min_y..=max_y {
+        let center = self.cell_bounds((x, y)).center();
+        if segment.distance_to_point(center) <= radius + half_diagonal {
+          self.insert_into_cell((x, y), object);
+        }
+      }
+    }
+  }
+
+  pub fn query_point(
+    &self,
+    point: Vec2,
+  ) -> Vec<ObjectRef> {
+    self.cells.get(&self.cell_of(point)).cloned().unwrap_or_default()
+  }
+
+  pub fn query_aabb(
+    &self,
+    bounds: &Aabb,
+  ) -> Vec<ObjectRef> {
+    let (min_x, min_y) = self.cell_of(bounds.min);
+    let (max_x, max_y) = self.cell_of(bounds.max);
+
+    let mut found = Vec::new();
+    for x in min_x..=max_x {
+      for y in min_y..=max_y {
+        if let Some(objects) = self.cells.get(&(x, y)) {
+          found.extend_from_slice(objects);
+        }
+      }
+    }
+
+    found.sort_unstable();
+    found.dedup();
+    found
+  }
+
+  /// Objects in every cell the segment passes through, walked cell by cell
+  /// (Amanatides & Woo) so a long motion step doesn't scan its bounding box.
+  pub fn query_segment(
+    &self,
+    segment: &Segment,
+  ) -> Vec<ObjectRef> {
+    let mut found = Vec::new();
+    for cell in self.cells_along(segment) {
+      if let Some(objects) = self.cells.get(&cell) {
+        found.extend_from_slice(objects);
+      }
+    }
+
+    found.sort_unstable();
+    found.dedup();
+    found
+  }
+
+  fn cells_along(
+    &self,
+    segment: &Segment,
+  ) -> Vec<(i32, i32)> {
+    let start = self.cell_of(segment.start);
+    let end = self.cell_of(segment.end);
+    let direction = segment.direction();
+
+    let step = |d: f32| if d > 0.0 {1} else if d < 0.0 {-1} else {0};
+    let (step_x, step_y) = (step(direction.x), step(direction.y));
+
+    // Parameter along the segment at which the next cell boundary is hit on
+    // each axis, and how far apart successive boundaries are.
+    let boundary = |cell: i32, s: i32, origin: f32, d: f32| {
+      if s == 0 {
+        f32::INFINITY
+      } else {
+        let edge = (cell + if s > 0 {1} else {0}) as f32 * self.cell_size;
+        (edge - origin) / d
+      }
+    };
+    let delta = |d: f32| if d == 0.0 {f32::INFINITY} else {self.cell_size / d.abs()};
+
+    let mut t_max_x = boundary(start.0, step_x, segment.start.x, direction.x);
+    let mut t_max_y = boundary(start.1, step_y, segment.start.y, direction.y);
+    let (t_delta_x, t_delta_y) = (delta(direction.x), delta(direction.y));
+
+    let mut cell = start;
+    let mut cells = vec![cell];
+    let max_steps = (start.0 - end.0).abs() + (start.1 - end.1).abs();
+
+    for _ in 0..max_steps {
+      if t_max_x < t_max_y {
+        cell.0 += step_x;
+        t_max_x += t_delta_x;
+      } else {
+        cell.1 += step_y;
+        t_max_y += t_delta_y;
+      }
+      cells.push(cell);
+    }
+
+    cells
+  }
+}
+
+impl Default for SpatialIndex {
+  fn default() -> SpatialIndex {
+    SpatialIndex::new(DEFAULT_CELL_SIZE)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::attractor::Attractor;
+  use super::super::point::Point;
+  use super::super::ship::SHIP_RADIUS;
+  use super::super::zipper::Zipper;
+
+  fn level() -> Level {
+    let mut level = Level::default();
+    level.attractors.push(Attractor::new(&Point {x: 0.0, y: 0.0}, 150.0, 50.0, true));
+    level.attractors.push(Attractor::new(&Point {x: 2000.0, y: 2000.0}, 100.0, 40.0, false));
+    level.zippers.push(Zipper::new(&Point {x: -900.0, y: -75.0}, &Point {x: -200.0, y: -75.0}, 100.0, 100.0, 1.0));
+    level.zippers.push(Zipper::new(&Point {x: 0.0, y: 0.0}, &Point {x: 3000.0, y: 3000.0}, 50.0, 100.0, 1.0));
+    level
+  }
+
+  #[test]
+  fn point_query_finds_nearby_objects_only() {
+    let index = SpatialIndex::build(&level(), DEFAULT_CELL_SIZE, SHIP_RADIUS);
+
+    let near_origin = index.query_point(Vec2::new(10.0, 10.0));
+    assert!(near_origin.contains(&ObjectRef::Attractor(0)));
+    assert!(!near_origin.contains(&ObjectRef::Attractor(1)));
+
+    let near_far = index.query_point(Vec2::new(2010.0, 1990.0));
+    assert!(near_far.contains(&ObjectRef::Attractor(1)));
+    assert!(near_far.contains(&ObjectRef::Zipper(1)));
+    assert!(!near_far.contains(&ObjectRef::Attractor(0)));
+  }
+
+  #[test]
+  fn diagonal_zipper_skips_far_corners() {
+    let index = SpatialIndex::build(&level(), DEFAULT_CELL_SIZE, SHIP_RADIUS);
+    assert!(!index.query_point(Vec2::new(2900.0, 100.0)).contains(&ObjectRef::Zipper(1)));
+    assert!(index.query_point(Vec2::new(1500.0, 1500.0)).contains(&ObjectRef::Zipper(1)));
+  }
+
+  #[test]
+  fn segment_query_walks_every_crossed_cell() {
+    let index = SpatialIndex::build(&level(), DEFAULT_CELL_SIZE, SHIP_RADIUS);
+
+    let found = index.query_segment(&Segment::new(Vec2::new(-1000.0, 0.0), Vec2::new(-100.0, 0.0)));
+    assert!(found.contains(&ObjectRef::Attractor(0)));
+    assert!(found.contains(&ObjectRef::Zipper(0)));
+    assert!(!found.contains(&ObjectRef::Attractor(1)));
+
+    let found = index.query_segment(&Segment::new(Vec2::new(1000.0, -2000.0), Vec2::new(1000.0, -1000.0)));
+    assert!(found.is_empty());
+  }
+
+  #[test]
+  fn segment_cells_are_connected() {
+    let index = SpatialIndex::new(10.0);
+    let cells = index.cells_along(&Segment::new(Vec2::new(-35.0, 12.0), Vec2::new(47.0, -58.0)));
+
+    assert_eq!(cells.first(), Some(&(-4, 1)));
+    assert_eq!(cells.last(), Some(&(4, -6)));
+    for pair in cells.windows(2) {
+      let (a, b) = (pair[0], pair[1]);
+      assert_eq!((a.0 - b.0).abs() + (a.1 - b.1).abs(), 1);
+    }
+  }
+
+  #[test]
+  fn segment_query_matches_brute_force() {
+    let level = level();
+    let index = SpatialIndex::build(&level, 100.0, SHIP_RADIUS);
+
+    for i in 0..50 {
+      let angle = i as f32 * 0.37;
+      let start = Vec2::new(-1000.0 + i as f32 * 40.0, -500.0);
+      let segment = Segment::new(start, start + Vec2::from_angle(angle) * 1500.0);
+      let found = index.query_segment(&segment);
+
+      for (j, attractor) in level.attractors.iter().enumerate() {
+        let r = attractor.bounds().width() / 2.0;
+        if segment.distance_to_point(attractor.bounds().center()) < r {
+          assert!(found.contains(&ObjectRef::Attractor(j)));
+        }
+      }
+      for (j, zipper) in level.zippers.iter().enumerate() {
+        if zipper.segment().intersect(&segment).is_some() {
+          assert!(found.contains(&ObjectRef::Zipper(j)));
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn moving_objects_are_found_anywhere_on_their_track() {
+    use super::super::animation::{Path, Track};
+
+    let track = Track {path: Path::Linear {to: Point {x: 1500.0, y: 0.0}}, period: 1000.0, phase: 0.0, swing: None};
+    let mut level = Level::default();
+    level.attractors.push(Attractor::new(&Point {x: 0.0, y: 0.0}, 100.0, 40.0, true).with_track(track));
+    level.rebuild_index();
+
+    level.animate(500);
+    let pos: Vec2 = level.attractors[0].pos().into();
+    assert!(pos.approx_eq(Vec2::new(750.0, 0.0), 1e-3));
+    assert_eq!(level.attractors_near(pos).count(), 1);
+  }
+}
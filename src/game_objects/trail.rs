@@ -0,0 +1,117 @@
+use ggez::{graphics};
+use ggez::{Context, GameResult};
+
+use super::super::geometry::Vec2;
+use super::super::screen_context::ScreenContext;
+
+use std::collections::VecDeque;
+
+/// Positions kept by default: a bit over a second of ticks.
+pub const TRAIL_LENGTH: usize = 80;
+/// Width of the newest end of the trail, in game units.
+const TRAIL_WIDTH: f32 = 6.0;
+
+/// The ship's recent path, drawn as a polyline that narrows and fades
+/// towards its oldest end.
+pub struct Trail {
+  points: VecDeque<Vec2>,
+  capacity: usize,
+}
+
+impl Trail {
+  pub fn new(capacity: usize) -> Trail {
+    Trail {
+      points: VecDeque::with_capacity(capacity),
+      capacity,
+    }
+  }
+
+  pub fn push(
+    &mut self,
+    point: Vec2,
+  ) {
+    if self.points.len() == self.capacity {
+      self.points.pop_front();
+    }
+    self.points.push_back(point);
+  }
+
+  /// Forgets the path, e.g. after a teleport so no line is drawn across
+  /// the jump.
+  pub fn clear(&mut self) {
+    self.points.clear();
+  }
+
+  pub fn len(&self) -> usize {
+    self.points.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.points.is_empty()
+  }
+
+  pub fn points(&self) -> impl Iterator<Item = &Vec2> {
+    self.points.iter()
+  }
+
+  pub fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    if self.points.len() < 2 {
+      return Ok(());
+    }
+
+    // Each segment is its own polyline so width and alpha can step down
+    // along the trail; they all still go into one mesh.
+    let mut mesh = graphics::MeshBuilder::new();
+    let count = self.points.len() as f32;
+    let mut drawn = false;
+    for (i, (from, to)) in self.points.iter().zip(self.points.iter().skip(1)).enumerate() {
+      if from.approx_eq(*to, 1e-3) {
+        continue;
+      }
+
+      let fade = (i + 1) as f32 / count;
+      mesh.polyline(
+        graphics::DrawMode::stroke(screen.size_game_to_screen(TRAIL_WIDTH * fade).max(1.0)),
+        &[screen.point_game_to_screen((*from).into()), screen.point_game_to_screen((*to).into())],
+        [0.6, 0.8, 1.0, 0.5 * fade].into()
+      )?;
+      drawn = true;
+    }
+
+    if !drawn {
+      return Ok(());
+    }
+
+    let mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+}
+
+impl Default for Trail {
+  fn default() -> Trail {
+    Trail::new(TRAIL_LENGTH)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn keeps_only_the_newest_points() {
+    let mut trail = Trail::new(3);
+    for i in 0..5 {
+      trail.push(Vec2::new(i as f32, 0.0));
+    }
+
+    let xs: Vec<f32> = trail.points().map(|point| point.x).collect();
+    assert_eq!(xs, vec![2.0, 3.0, 4.0]);
+
+    trail.clear();
+    assert!(trail.is_empty());
+  }
+}
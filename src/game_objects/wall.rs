@@ -0,0 +1,205 @@
+use ggez::{graphics};
+use ggez::{Context, GameResult};
+
+use super::point::Point;
+use super::animation::{Animated, Pose, Track};
+use super::super::geometry::{Aabb, Segment, Vec2};
+use super::super::screen_context::ScreenContext;
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WallKind {
+  #[default]
+  Solid,
+  Bouncy,
+  Deadly,
+}
+
+/// A polyline obstacle. Solid and bouncy walls reflect the ship, keeping
+/// `restitution` of the speed into the wall; deadly walls destroy it.
+#[derive(Deserialize)]
+pub struct Wall {
+  points: Vec<Point>,
+  thickness: f32,
+  #[serde(default)]
+  kind: WallKind,
+  #[serde(default)]
+  restitution: Option<f32>,
+  #[serde(default)]
+  track: Option<Track>,
+  #[serde(skip)]
+  pose: Pose,
+}
+
+impl Wall {
+  pub fn new(
+    points: Vec<Point>,
+    thickness: f32,
+    kind: WallKind,
+  ) -> Wall {
+    Wall {
+      points,
+      thickness,
+      kind,
+      restitution: None,
+      track: None,
+      pose: Pose::IDENTITY,
+    }
+  }
+
+  pub fn with_track(mut self, track: Track) -> Wall {
+    self.track = Some(track);
+    self
+  }
+
+  pub fn kind(&self) -> WallKind {
+    self.kind
+  }
+
+  pub fn thickness(&self) -> f32 {
+    self.thickness
+  }
+
+  pub fn restitution(&self) -> f32 {
+    self.restitution.unwrap_or(match self.kind {
+      WallKind::Solid => 0.5,
+      WallKind::Bouncy => 1.0,
+      WallKind::Deadly => 0.0,
+    })
+  }
+
+  /// Current segments, including any animation.
+  pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+    let pose = self.pose;
+    self.points.windows(2).map(move |pair| Segment::new(pose.apply(pair[0].into()), pose.apply(pair[1].into())))
+  }
+
+  pub fn bounds(&self) -> Option<Aabb> {
+    bounds_of(self.segments(), self.thickness)
+  }
+
+  /// `bounds` over the wall's whole track.
+  pub fn swept_bounds(&self) -> Option<Aabb> {
+    let at_rest = self.points.windows(2).map(|pair| Segment::new(pair[0].into(), pair[1].into()));
+    bounds_of(at_rest, self.thickness).map(|bounds| Animated::swept_bounds(self, bounds))
+  }
+
+  /// Velocity after hitting the wall at a surface with the given outward
+  /// `normal`: the normal component is reversed and scaled by restitution,
+  /// the tangential component is kept.
+  pub fn reflect(
+    &self,
+    vel: Vec2,
+    normal: Vec2,
+  ) -> Vec2 {
+    let into_wall = vel.dot(normal);
+    if into_wall >= 0.0 {
+      vel
+    } else {
+      vel - normal * (into_wall * (1.0 + self.restitution()))
+    }
+  }
+
+  pub fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    if self.points.len() < 2 {
+      return Ok(());
+    }
+
+    let color: graphics::Color = match self.kind {
+      WallKind::Solid => [0.6, 0.6, 0.65, 1.0].into(),
+      WallKind::Bouncy => [0.2, 0.8, 1.0, 1.0].into(),
+      WallKind::Deadly => [1.0, 0.2, 0.2, 1.0].into(),
+    };
+
+    let points: Vec<mint::Point2<f32>> = self.points.iter()
+      .map(|point| screen.point_game_to_screen(self.pose.apply((*point).into()).into()))
+      .collect();
+
+    let mut mesh = graphics::MeshBuilder::new();
+    mesh.polyline(
+      graphics::DrawMode::Stroke(
+        graphics::StrokeOptions::default()
+          .with_line_width(screen.size_game_to_screen(self.thickness))
+          .with_line_join(graphics::LineJoin::Round)
+          .with_line_cap(graphics::LineCap::Round)
+      ),
+      &points,
+      color
+    )?;
+
+    let built_mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &built_mesh, graphics::DrawParam::default())
+  }
+}
+
+impl Animated for Wall {
+  fn track(&self) -> Option<&Track> {
+    self.track.as_ref()
+  }
+
+  fn set_pose(&mut self, pose: Pose) {
+    self.pose = pose;
+  }
+}
+
+/// Box around `segments`, widened by half the wall's `thickness`.
+fn bounds_of(
+  segments: impl Iterator<Item = Segment>,
+  thickness: f32,
+) -> Option<Aabb> {
+  segments
+    .map(|segment| Aabb::from_points(segment.start, segment.end))
+    .reduce(|a, b| a.union(&b))
+    .map(|bounds| bounds.expand(thickness / 2.0))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn wall(kind: WallKind) -> Wall {
+    Wall::new(vec![Point {x: 0.0, y: 0.0}, Point {x: 100.0, y: 0.0}, Point {x: 100.0, y: 100.0}], 10.0, kind)
+  }
+
+  #[test]
+  fn segments_follow_polyline() {
+    let segments: Vec<Segment> = wall(WallKind::Solid).segments().collect();
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[1].start, Vec2::new(100.0, 0.0));
+    assert_eq!(wall(WallKind::Solid).bounds(), Some(Aabb::new(Vec2::new(-5.0, -5.0), Vec2::new(105.0, 105.0))));
+  }
+
+  #[test]
+  fn bouncy_reflection_keeps_speed() {
+    let reflected = wall(WallKind::Bouncy).reflect(Vec2::new(3.0, 4.0), Vec2::new(0.0, -1.0));
+    assert_eq!(reflected, Vec2::new(3.0, -4.0));
+  }
+
+  #[test]
+  fn solid_reflection_loses_normal_speed() {
+    let reflected = wall(WallKind::Solid).reflect(Vec2::new(3.0, 4.0), Vec2::new(0.0, -1.0));
+    assert_eq!(reflected, Vec2::new(3.0, -2.0));
+  }
+
+  #[test]
+  fn moving_away_is_untouched() {
+    let reflected = wall(WallKind::Solid).reflect(Vec2::new(3.0, -4.0), Vec2::new(0.0, -1.0));
+    assert_eq!(reflected, Vec2::new(3.0, -4.0));
+  }
+
+  #[test]
+  fn kind_defaults_to_solid_in_json() {
+    let wall: Wall = serde_json::from_str(r#"{"points":[{"x":0,"y":0},{"x":1,"y":1}],"thickness":4}"#).unwrap();
+    assert_eq!(wall.kind(), WallKind::Solid);
+
+    let wall: Wall = serde_json::from_str(r#"{"points":[],"thickness":4,"kind":"deadly","restitution":0.2}"#).unwrap();
+    assert_eq!(wall.kind(), WallKind::Deadly);
+    assert_eq!(wall.restitution(), 0.2);
+  }
+}
@@ -2,10 +2,17 @@ use ggez::{graphics};
 use ggez::{Context, GameResult};
 
 use super::point::Point;
+use super::animation::{Animated, Pose, Track};
 use super::super::screen_context::ScreenContext;
+use super::super::geometry::{Aabb, Segment, Vec2, EPSILON};
 
 use serde::Deserialize;
 
+/// Distance between chevrons drawn along a zipper, in game units.
+const CHEVRON_SPACING: f32 = 60.0;
+/// How fast chevrons slide along a zipper of strength 1, in units per ms.
+const CHEVRON_SPEED: f32 = 0.1;
+
 #[derive(Deserialize)]
 pub struct DirectionalLine {
   start_point: Point,
@@ -53,85 +60,37 @@ impl DirectionalLine {
     (slope, int)
   }
 
+  pub fn segment(&self) -> Segment {
+    Segment::new(self.start_point.into(), self.end_point.into())
+  }
+
+  pub fn start_point(&self) -> Point {
+    self.start_point
+  }
+
+  pub fn end_point(&self) -> Point {
+    self.end_point
+  }
+
   pub fn point_is_on_line(
     &self,
     point: &Point,
   ) -> bool {
-    let x_bounds = if self.start_point.x < self.end_point.x {
-      (self.start_point.x, self.end_point.x)
-    } else {
-      (self.end_point.x, self.start_point.x)
-    };
-
-    let y_bounds = if self.start_point.y < self.end_point.y {
-      (self.start_point.y, self.end_point.y)
-    } else {
-      (self.end_point.y, self.start_point.y)
-    };
-
-    let x_in_bounds = x_bounds.0 <= point.x && point.x <= x_bounds.1;
-    let y_in_bounds = y_bounds.0 <= point.y && point.y <= y_bounds.1;
-    
-
-    let s_i_f = if self.is_vertical() {
-      self.slope_int_format()
-    } else {
-      (0.0, point.y)
-    };
-
-    let on_line = point.y == point.x * s_i_f.0 + s_i_f.1;
-
-    x_in_bounds && y_in_bounds && on_line
+    self.segment().contains_point((*point).into(), EPSILON * (1.0 + self.length()))
   }
 
   pub fn calc_line_intersect(
     &self,
     other_line: &DirectionalLine,
   ) -> Option<Point> {
-    if self.length() == 0.0 || other_line.length() == 0.0 {
-      return None;
-    }
-
-    if self.is_vertical() && other_line.is_vertical() {
-      None
-    } else if self.is_vertical() || other_line.is_vertical() {
-      if self.is_vertical() {
-        let s_i_f = other_line.slope_int_format();
-        let y_coord = s_i_f.0 * self.start_point.x + s_i_f.1;
-        Some(Point {x: self.start_point.x, y: y_coord})
-      } else  {
-        let s_i_f = self.slope_int_format();
-        let y_coord = s_i_f.0 * other_line.start_point.x + s_i_f.1;
-        Some(Point {x: other_line.start_point.x, y: y_coord})
-      }
-    } else {
-      let self_s_i_f = self.slope_int_format();
-      let other_s_i_f = other_line.slope_int_format();
-
-      if self_s_i_f.0 == other_s_i_f.0 {
-        None
-      } else {
-        let x_coord = (self_s_i_f.1 - other_s_i_f.1) / (self_s_i_f.0 - other_s_i_f.0);
-        let y_coord = self_s_i_f.0 * x_coord + self_s_i_f.1;
-        Some(Point {x: x_coord, y: y_coord})
-      }
-    }
+    self.segment().line_intersect(&other_line.segment()).map(Point::from)
   }
 
   pub fn calc_line_seg_intersect(
     &self,
     other_line: &DirectionalLine,
   ) -> Option<Point> {
-    match self.calc_line_intersect(other_line) {
-      Some(point) => {
-        if self.point_is_on_line(&point) && other_line.point_is_on_line(&point) {
-          Some(point)
-        } else {
-          None
-        }
-      }
-      None => None
-    }
+    self.segment().intersect(&other_line.segment()).map(Point::from)
   }
 
   pub fn get_vector_point(&self) -> Point {
@@ -162,7 +121,11 @@ pub struct Zipper {
   line: DirectionalLine,
   width: f32,
   leading_dist: f32,
-  pub strength: f32
+  pub strength: f32,
+  #[serde(default)]
+  track: Option<Track>,
+  #[serde(skip)]
+  pose: Pose,
 }
 
 #[allow(clippy::float_cmp)]
@@ -179,38 +142,50 @@ impl Zipper {
       width,
       leading_dist,
       strength,
+      track: None,
+      pose: Pose::IDENTITY,
     }
   }
 
+  pub fn with_track(mut self, track: Track) -> Zipper {
+    self.track = Some(track);
+    self
+  }
+
   pub fn length(&self) -> f32 {
     self.line.length()
   }
 
+  /// Line from `point` to the foot of its perpendicular on the zipper's
+  /// (infinite) line.
   pub fn get_perp_through_point(
     &self,
     point: &Point
   ) -> DirectionalLine {
-    if self.line.is_vertical() {
-      DirectionalLine::new(point, &Point {x: self.line.start_point.x, y: point.y})
-    } else if self.line.start_point.y == self.line.end_point.y {
-      DirectionalLine::new(point, &Point {x: point.x, y: self.line.start_point.y})
-    } else {
-      let s_i_f = self.line.slope_int_format();
+    let foot = self.segment().project_point((*point).into());
+    DirectionalLine::new(point, &foot.into())
+  }
 
-      let perp_slope = -1.0 / s_i_f.0;
+  /// Current line, including any animation.
+  pub fn segment(&self) -> Segment {
+    let segment = self.line.segment();
+    Segment::new(self.pose.apply(segment.start), self.pose.apply(segment.end))
+  }
 
-      let perp_s_i_f = (perp_slope, -point.x * perp_slope + point.y);
+  pub fn width(&self) -> f32 {
+    self.width
+  }
 
-      let perp_dir_line = DirectionalLine::new(
-        &Point {x: 0.0,   y: perp_s_i_f.1},
-        &Point {x: 100.0, y: perp_s_i_f.1 + 100.0 * perp_s_i_f.0},
-      );
+  /// The band `point_in_range` accepts, widened to a box.
+  pub fn bounds(&self) -> Aabb {
+    let segment = self.segment();
+    Aabb::from_points(segment.start, segment.end).expand(self.width)
+  }
 
-      DirectionalLine::new(
-        point,
-        &self.line.calc_line_intersect(&perp_dir_line).unwrap_or(Point {x: 0.0, y: 0.0})
-      )
-    }
+  /// `bounds` over the zipper's whole track.
+  pub fn swept_bounds(&self) -> Aabb {
+    let segment = self.line.segment();
+    Animated::swept_bounds(self, Aabb::from_points(segment.start, segment.end).expand(self.width))
   }
 
   pub fn advance_line(
@@ -231,15 +206,369 @@ impl Zipper {
     &self,
     point: &Point,
   ) -> bool {
-    let perpendicular_line = self.get_perp_through_point(point);
-    self.line.point_is_on_line(&perpendicular_line.end_point) && perpendicular_line.length() < self.width
+    let segment = self.segment();
+    let t = segment.project((*point).into());
+    let perp_length = segment.point_at(t).distance((*point).into());
+    (-EPSILON..=1.0 + EPSILON).contains(&t) && perp_length < self.width
+  }
+
+  /// Part of `motion` inside the band `point_in_range` accepts, as
+  /// parameters from 0 to 1, even if both ends of the motion lie outside
+  /// it. `None` if the motion misses, or the zipper has no direction.
+  pub fn band_interval(
+    &self,
+    motion: &Segment,
+  ) -> Option<(f32, f32)> {
+    let segment = self.segment();
+    let axis = segment.direction();
+    let length_squared = axis.length_squared();
+    if length_squared <= EPSILON * EPSILON {
+      return None;
+    }
+    let normal = axis.perp() / length_squared.sqrt();
+
+    // Along the zipper and across it, as linear functions of the motion
+    // parameter; the band is where both are in range.
+    let offset = motion.start - segment.start;
+    let step = motion.direction();
+    let along = (offset.dot(axis) / length_squared, step.dot(axis) / length_squared);
+    let across = (offset.dot(normal), step.dot(normal));
+
+    let (enter, exit) = clip_slab((0.0, 1.0), along, -EPSILON, 1.0 + EPSILON)?;
+    clip_slab((enter, exit), across, -self.width, self.width)
+  }
+
+  pub fn leading_dist(&self) -> f32 {
+    self.leading_dist
   }
 
+  /// Draws the capture band, the `leading_dist` run-off past the end, and
+  /// chevrons sliding along the direction at a speed set by `strength`.
+  /// `millis` drives the chevrons.
   pub fn draw(
     &self,
     ctx: &mut Context,
     screen: &ScreenContext,
+    millis: u64,
   ) -> GameResult<()> {
-    self.line.draw(ctx, screen)
+    let segment = self.segment();
+    let length = segment.length();
+    let direction = match segment.direction().try_normalize() {
+      Some(direction) => direction,
+      None => return Ok(()),
+    };
+    let side = direction.perp() * self.width;
+    let to_screen = |point: Vec2| screen.point_game_to_screen(point.into());
+
+    let mut mesh = graphics::MeshBuilder::new();
+
+    let band = [
+      to_screen(segment.start + side),
+      to_screen(segment.end + side),
+      to_screen(segment.end - side),
+      to_screen(segment.start - side),
+    ];
+    mesh.polygon(graphics::DrawMode::fill(), &band, [0.2, 0.8, 1.0, 0.12].into())?;
+    mesh.polygon(graphics::DrawMode::stroke(1.0), &band, [0.2, 0.8, 1.0, 0.35].into())?;
+
+    if self.leading_dist > 0.0 {
+      let tip = segment.end + direction * self.leading_dist;
+      let run_off = [
+        to_screen(segment.end + side),
+        to_screen(tip + side * 0.3),
+        to_screen(tip - side * 0.3),
+        to_screen(segment.end - side),
+      ];
+      mesh.polygon(graphics::DrawMode::fill(), &run_off, [0.2, 0.8, 1.0, 0.05].into())?;
+      mesh.line(&[to_screen(segment.end), to_screen(tip)], 1.0, [1.0, 1.0, 1.0, 0.3].into())?;
+    }
+
+    let phase = (millis as f32 * CHEVRON_SPEED * self.strength).rem_euclid(CHEVRON_SPACING);
+    let half_width = side * 0.5;
+    let depth = direction * (self.width * 0.4);
+    let mut dist = phase;
+    while dist < length {
+      // Fade in and out at the ends so chevrons don't pop.
+      let edge = (dist.min(length - dist) / CHEVRON_SPACING).min(1.0);
+      let tip = segment.start + direction * dist;
+      mesh.polyline(
+        graphics::DrawMode::stroke(screen.size_game_to_screen(5.0).max(1.0)),
+        &[to_screen(tip - depth + half_width), to_screen(tip), to_screen(tip - depth - half_width)],
+        [0.6, 0.95, 1.0, 0.6 * edge].into()
+      )?;
+      dist += CHEVRON_SPACING;
+    }
+
+    mesh.line(&[to_screen(segment.start), to_screen(segment.end)], 3.0, [1.0, 1.0, 1.0, 1.0].into())?;
+
+    let built_mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &built_mesh, graphics::DrawParam::default())
+  }
+}
+
+impl Animated for Zipper {
+  fn track(&self) -> Option<&Track> {
+    self.track.as_ref()
+  }
+
+  fn set_pose(&mut self, pose: Pose) {
+    self.pose = pose;
+  }
+}
+
+/// Narrows `range` to the parameters `s` where `lo <= a + b * s <= hi`,
+/// with `(a, b)` given as `value`.
+fn clip_slab(
+  range: (f32, f32),
+  value: (f32, f32),
+  lo: f32,
+  hi: f32,
+) -> Option<(f32, f32)> {
+  let (a, b) = value;
+  let (enter, exit) = if b.abs() <= EPSILON * EPSILON {
+    if a < lo || a > hi {
+      return None;
+    }
+    range
+  } else {
+    let (s0, s1) = ((lo - a) / b, (hi - a) / b);
+    (range.0.max(s0.min(s1)), range.1.min(s0.max(s1)))
+  };
+
+  if enter <= exit {
+    Some((enter, exit))
+  } else {
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::super::geometry::Vec2;
+
+  use proptest::prelude::*;
+
+  fn p(x: f32, y: f32) -> Point {
+    Point {x, y}
+  }
+
+  fn line(x0: f32, y0: f32, x1: f32, y1: f32) -> DirectionalLine {
+    DirectionalLine::new(&p(x0, y0), &p(x1, y1))
+  }
+
+  fn zipper(x0: f32, y0: f32, x1: f32, y1: f32, width: f32) -> Zipper {
+    Zipper::new(&p(x0, y0), &p(x1, y1), width, 100.0, 1.0)
+  }
+
+  fn close(a: Point, b: Point, tolerance: f32) -> bool {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt() <= tolerance
+  }
+
+  #[test]
+  fn seg_intersect_diagonals() {
+    let hit = line(0.0, 0.0, 10.0, 10.0).calc_line_seg_intersect(&line(0.0, 10.0, 10.0, 0.0));
+    assert!(close(hit.unwrap(), p(5.0, 5.0), 1e-3));
+  }
+
+  #[test]
+  fn seg_intersect_vertical_and_horizontal() {
+    let vertical = line(75.0, 200.0, 75.0, 500.0);
+    let horizontal = line(0.0, 300.0, 100.0, 300.0);
+    assert!(close(vertical.calc_line_seg_intersect(&horizontal).unwrap(), p(75.0, 300.0), 1e-3));
+    assert!(close(horizontal.calc_line_seg_intersect(&vertical).unwrap(), p(75.0, 300.0), 1e-3));
+  }
+
+  #[test]
+  fn seg_intersect_misses_outside_segment() {
+    let vertical = line(75.0, 200.0, 75.0, 500.0);
+    let horizontal = line(0.0, 100.0, 100.0, 100.0);
+    assert_eq!(vertical.calc_line_seg_intersect(&horizontal), None);
+  }
+
+  #[test]
+  fn seg_intersect_parallel_verticals() {
+    assert_eq!(line(0.0, 0.0, 0.0, 10.0).calc_line_seg_intersect(&line(1.0, 0.0, 1.0, 10.0)), None);
+  }
+
+  #[test]
+  fn seg_intersect_zero_length() {
+    let dot = line(5.0, 5.0, 5.0, 5.0);
+    assert!(dot.calc_line_seg_intersect(&line(0.0, 5.0, 10.0, 5.0)).is_some());
+    assert_eq!(dot.calc_line_seg_intersect(&line(0.0, 6.0, 10.0, 6.0)), None);
+    assert_eq!(dot.calc_line_intersect(&line(0.0, 5.0, 10.0, 5.0)), None);
+  }
+
+  #[test]
+  fn point_on_vertical_line() {
+    let vertical = line(3.0, 0.0, 3.0, 10.0);
+    assert!(vertical.point_is_on_line(&p(3.0, 4.0)));
+    assert!(!vertical.point_is_on_line(&p(4.0, 4.0)));
+    assert!(!vertical.point_is_on_line(&p(3.0, 11.0)));
+  }
+
+  #[test]
+  fn point_on_sloped_line() {
+    let sloped = line(0.0, 0.0, 10.0, 5.0);
+    assert!(sloped.point_is_on_line(&p(4.0, 2.0)));
+    assert!(!sloped.point_is_on_line(&p(4.0, 3.0)));
+  }
+
+  #[test]
+  fn perp_through_point_on_axis_aligned_zippers() {
+    let horizontal = zipper(-900.0, -75.0, -200.0, -75.0, 100.0);
+    let perp = horizontal.get_perp_through_point(&p(-500.0, 0.0));
+    assert!(close(perp.end_point(), p(-500.0, -75.0), 1e-3));
+    assert!((perp.length() - 75.0).abs() < 1e-3);
+
+    let vertical = zipper(75.0, 200.0, 75.0, 500.0, 100.0);
+    let perp = vertical.get_perp_through_point(&p(0.0, 300.0));
+    assert!(close(perp.end_point(), p(75.0, 300.0), 1e-3));
+  }
+
+  #[test]
+  fn perp_through_point_on_diagonal_zipper() {
+    let diagonal = zipper(0.0, 0.0, 10.0, 10.0, 100.0);
+    let perp = diagonal.get_perp_through_point(&p(10.0, 0.0));
+    assert!(close(perp.start_point(), p(10.0, 0.0), 1e-6));
+    assert!(close(perp.end_point(), p(5.0, 5.0), 1e-3));
+  }
+
+  #[test]
+  fn perp_through_point_on_degenerate_zipper() {
+    let dot = zipper(2.0, 2.0, 2.0, 2.0, 100.0);
+    let perp = dot.get_perp_through_point(&p(7.0, 9.0));
+    assert!(close(perp.end_point(), p(2.0, 2.0), 1e-6));
+  }
+
+  #[test]
+  fn point_in_range_of_level_zippers() {
+    let horizontal = zipper(-900.0, -75.0, -200.0, -75.0, 100.0);
+    assert!(horizontal.point_in_range(&p(-500.0, -20.0)));
+    assert!(!horizontal.point_in_range(&p(-500.0, 30.0)));
+    assert!(!horizontal.point_in_range(&p(-100.0, -75.0)));
+
+    let vertical = zipper(75.0, 200.0, 75.0, 500.0, 100.0);
+    assert!(vertical.point_in_range(&p(100.0, 300.0)));
+    assert!(!vertical.point_in_range(&p(100.0, 600.0)));
+    assert!(!vertical.point_in_range(&p(200.0, 300.0)));
+  }
+
+  #[test]
+  fn point_in_range_of_degenerate_zipper() {
+    let dot = zipper(0.0, 0.0, 0.0, 0.0, 10.0);
+    assert!(dot.point_in_range(&p(3.0, 4.0)));
+    assert!(!dot.point_in_range(&p(30.0, 40.0)));
+  }
+
+  #[test]
+  fn band_interval_spans_a_crossing() {
+    let band = zipper(0.0, 0.0, 1000.0, 0.0, 10.0);
+
+    // Straight across in one step, both ends outside.
+    let (enter, exit) = band.band_interval(&Segment::new(Vec2::new(500.0, -50.0), Vec2::new(500.0, 50.0))).unwrap();
+    assert!((enter - 0.4).abs() < 1e-4 && (exit - 0.6).abs() < 1e-4);
+
+    // Starting inside and leaving past the end.
+    let (enter, exit) = band.band_interval(&Segment::new(Vec2::new(900.0, 0.0), Vec2::new(1100.0, 0.0))).unwrap();
+    assert!(enter == 0.0 && (exit - 0.5).abs() < 1e-3);
+
+    assert_eq!(band.band_interval(&Segment::new(Vec2::new(-50.0, -50.0), Vec2::new(-50.0, 50.0))), None);
+    assert_eq!(band.band_interval(&Segment::new(Vec2::new(500.0, 20.0), Vec2::new(600.0, 20.0))), None);
+    assert_eq!(zipper(0.0, 0.0, 0.0, 0.0, 10.0).band_interval(&Segment::new(Vec2::ZERO, Vec2::new(1.0, 0.0))), None);
   }
-}
\ No newline at end of file
+
+  fn coord() -> impl Strategy<Value = f32> {
+    -1000.0f32..1000.0
+  }
+
+  fn point() -> impl Strategy<Value = Point> {
+    (coord(), coord()).prop_map(|(x, y)| p(x, y))
+  }
+
+  proptest! {
+    #[test]
+    fn seg_intersect_lies_on_both_lines(a in point(), b in point(), c in point(), d in point()) {
+      let first = DirectionalLine::new(&a, &b);
+      let second = DirectionalLine::new(&c, &d);
+
+      if let Some(hit) = first.calc_line_seg_intersect(&second) {
+        let tolerance = 1e-2 * (1.0 + first.length().max(second.length()));
+        prop_assert!(first.segment().distance_to_point(hit.into()) <= tolerance);
+        prop_assert!(second.segment().distance_to_point(hit.into()) <= tolerance);
+      }
+    }
+
+    #[test]
+    fn seg_intersect_is_symmetric(a in point(), b in point(), c in point(), d in point()) {
+      let first = DirectionalLine::new(&a, &b);
+      let second = DirectionalLine::new(&c, &d);
+
+      let forward = first.calc_line_seg_intersect(&second);
+      let backward = second.calc_line_seg_intersect(&first);
+
+      // Only transversal crossings are compared; collinear overlaps report
+      // the point nearest each line's own start.
+      if first.segment().line_intersect(&second.segment()).is_some() {
+        prop_assert_eq!(forward.is_some(), backward.is_some());
+        if let (Some(f), Some(b)) = (forward, backward) {
+          prop_assert!(close(f, b, 1e-2 * (1.0 + first.length().max(second.length()))));
+        }
+      }
+    }
+
+    #[test]
+    fn crossing_through_midpoints_is_found(mid in point(), angle_a in 0.0f32..3.1, angle_b in 0.0f32..3.1, len in 1.0f32..500.0) {
+      prop_assume!((angle_a - angle_b).abs() > 0.05);
+
+      let offset = |angle: f32| p(angle.cos() * len, angle.sin() * len);
+      let (oa, ob) = (offset(angle_a), offset(angle_b));
+
+      let first = DirectionalLine::new(&p(mid.x - oa.x, mid.y - oa.y), &p(mid.x + oa.x, mid.y + oa.y));
+      let second = DirectionalLine::new(&p(mid.x - ob.x, mid.y - ob.y), &p(mid.x + ob.x, mid.y + ob.y));
+
+      let hit = first.calc_line_seg_intersect(&second);
+      prop_assert!(hit.is_some());
+      prop_assert!(close(hit.unwrap(), mid, 1e-1));
+    }
+
+    #[test]
+    fn perp_is_perpendicular_and_shortest(a in point(), b in point(), q in point()) {
+      let z = Zipper::new(&a, &b, 100.0, 100.0, 1.0);
+      prop_assume!(z.length() > 1.0);
+
+      let perp = z.get_perp_through_point(&q);
+      let along = z.line.segment().direction().normalize();
+      let across = Vec2::from(perp.end_point()) - q.into();
+
+      prop_assert!(along.dot(across).abs() <= 1e-2 * (1.0 + across.length()));
+      prop_assert!(z.line.segment().project_point(q.into()).distance(perp.end_point().into()) <= 1e-2);
+    }
+
+    #[test]
+    fn point_in_range_matches_band(a in point(), b in point(), t in 0.0f32..1.0, side in -1.0f32..1.0, width in 1.0f32..200.0) {
+      let z = Zipper::new(&a, &b, width, 100.0, 1.0);
+      prop_assume!(z.length() > 1.0);
+
+      let segment = z.line.segment();
+      let normal = segment.direction().perp().normalize();
+      let q = segment.point_at(t) + normal * (side * width * 0.99);
+
+      prop_assert!(z.point_in_range(&q.into()));
+
+      let outside = segment.point_at(t) + normal * (width * 1.01 * if side < 0.0 {-1.0} else {1.0});
+      prop_assert!(!z.point_in_range(&outside.into()));
+    }
+
+    #[test]
+    fn point_in_range_excludes_beyond_ends(a in point(), b in point(), beyond in 0.05f32..2.0) {
+      let z = Zipper::new(&a, &b, 100.0, 100.0, 1.0);
+      prop_assume!(z.length() > 1.0);
+
+      let q = z.line.segment().point_at(1.0 + beyond);
+      prop_assert!(!z.point_in_range(&q.into()));
+      let q = z.line.segment().point_at(-beyond);
+      prop_assert!(!z.point_in_range(&q.into()));
+    }
+  }
+}
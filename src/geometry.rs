@@ -0,0 +1,648 @@
+use super::game_objects::point::Point;
+
+use serde::{Deserialize, Serialize};
+
+use std::ops::{Add, AddAssign, Sub, SubAssign, Mul, Div, Neg};
+
+/// Tolerance used for parallel tests and for accepting intersections that
+/// land just past a segment's endpoints because of rounding.
+pub const EPSILON: f32 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Vec2 {
+  pub x: f32,
+  pub y: f32,
+}
+
+impl Vec2 {
+  pub const ZERO: Vec2 = Vec2 {x: 0.0, y: 0.0};
+
+  pub fn new(x: f32, y: f32) -> Vec2 {
+    Vec2 {x, y}
+  }
+
+  pub fn from_angle(angle: f32) -> Vec2 {
+    Vec2 {x: angle.cos(), y: angle.sin()}
+  }
+
+  pub fn dot(self, other: Vec2) -> f32 {
+    self.x * other.x + self.y * other.y
+  }
+
+  /// The z component of the 3D cross product; positive when `other` is
+  /// clockwise of `self` in screen space (y down).
+  pub fn cross(self, other: Vec2) -> f32 {
+    self.x * other.y - self.y * other.x
+  }
+
+  pub fn length_squared(self) -> f32 {
+    self.dot(self)
+  }
+
+  pub fn length(self) -> f32 {
+    self.length_squared().sqrt()
+  }
+
+  pub fn distance(self, other: Vec2) -> f32 {
+    (self - other).length()
+  }
+
+  /// `None` for vectors too short to have a meaningful direction.
+  pub fn try_normalize(self) -> Option<Vec2> {
+    let length = self.length();
+    if length <= EPSILON {
+      None
+    } else {
+      Some(self / length)
+    }
+  }
+
+  pub fn normalize(self) -> Vec2 {
+    self.try_normalize().unwrap_or(Vec2::ZERO)
+  }
+
+  pub fn rotate(self, angle: f32) -> Vec2 {
+    let (sin, cos) = angle.sin_cos();
+    Vec2 {
+      x: self.x * cos - self.y * sin,
+      y: self.x * sin + self.y * cos,
+    }
+  }
+
+  /// `self` rotated a quarter turn.
+  pub fn perp(self) -> Vec2 {
+    Vec2 {x: -self.y, y: self.x}
+  }
+
+  pub fn angle(self) -> f32 {
+    self.y.atan2(self.x)
+  }
+
+  pub fn lerp(self, other: Vec2, t: f32) -> Vec2 {
+    self + (other - self) * t
+  }
+
+  pub fn approx_eq(self, other: Vec2, tolerance: f32) -> bool {
+    self.distance(other) <= tolerance
+  }
+}
+
+impl Add for Vec2 {
+  type Output = Vec2;
+  fn add(self, other: Vec2) -> Vec2 {
+    Vec2 {x: self.x + other.x, y: self.y + other.y}
+  }
+}
+
+impl AddAssign for Vec2 {
+  fn add_assign(&mut self, other: Vec2) {
+    *self = *self + other;
+  }
+}
+
+impl Sub for Vec2 {
+  type Output = Vec2;
+  fn sub(self, other: Vec2) -> Vec2 {
+    Vec2 {x: self.x - other.x, y: self.y - other.y}
+  }
+}
+
+impl SubAssign for Vec2 {
+  fn sub_assign(&mut self, other: Vec2) {
+    *self = *self - other;
+  }
+}
+
+impl Mul<f32> for Vec2 {
+  type Output = Vec2;
+  fn mul(self, scalar: f32) -> Vec2 {
+    Vec2 {x: self.x * scalar, y: self.y * scalar}
+  }
+}
+
+impl Div<f32> for Vec2 {
+  type Output = Vec2;
+  fn div(self, scalar: f32) -> Vec2 {
+    Vec2 {x: self.x / scalar, y: self.y / scalar}
+  }
+}
+
+impl Neg for Vec2 {
+  type Output = Vec2;
+  fn neg(self) -> Vec2 {
+    Vec2 {x: -self.x, y: -self.y}
+  }
+}
+
+impl From<Point> for Vec2 {
+  fn from(item: Point) -> Self {
+    Vec2 {x: item.x, y: item.y}
+  }
+}
+
+impl From<Vec2> for Point {
+  fn from(item: Vec2) -> Self {
+    Point {x: item.x, y: item.y}
+  }
+}
+
+impl From<mint::Point2<f32>> for Vec2 {
+  fn from(item: mint::Point2<f32>) -> Self {
+    Vec2 {x: item.x, y: item.y}
+  }
+}
+
+impl From<Vec2> for mint::Point2<f32> {
+  fn from(item: Vec2) -> Self {
+    mint::Point2 {x: item.x, y: item.y}
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+  pub start: Vec2,
+  pub end: Vec2,
+}
+
+impl Segment {
+  pub fn new(start: Vec2, end: Vec2) -> Segment {
+    Segment {start, end}
+  }
+
+  pub fn direction(&self) -> Vec2 {
+    self.end - self.start
+  }
+
+  pub fn length(&self) -> f32 {
+    self.direction().length()
+  }
+
+  pub fn is_degenerate(&self) -> bool {
+    self.length() <= EPSILON
+  }
+
+  pub fn point_at(&self, t: f32) -> Vec2 {
+    self.start.lerp(self.end, t)
+  }
+
+  /// Parameter of `point`'s projection onto the infinite line through the
+  /// segment: 0 at `start`, 1 at `end`. Degenerate segments project to 0.
+  pub fn project(&self, point: Vec2) -> f32 {
+    let direction = self.direction();
+    let length_squared = direction.length_squared();
+    if length_squared <= EPSILON * EPSILON {
+      0.0
+    } else {
+      (point - self.start).dot(direction) / length_squared
+    }
+  }
+
+  /// Foot of the perpendicular from `point` to the infinite line.
+  pub fn project_point(&self, point: Vec2) -> Vec2 {
+    self.point_at(self.project(point))
+  }
+
+  pub fn closest_point(&self, point: Vec2) -> Vec2 {
+    self.point_at(self.project(point).clamp(0.0, 1.0))
+  }
+
+  pub fn distance_to_point(&self, point: Vec2) -> f32 {
+    self.closest_point(point).distance(point)
+  }
+
+  pub fn contains_point(&self, point: Vec2, tolerance: f32) -> bool {
+    self.distance_to_point(point) <= tolerance
+  }
+
+  /// Intersection of the infinite lines through both segments, as the
+  /// parameters along `self` and `other`. `None` if they are parallel.
+  pub fn line_intersect_params(&self, other: &Segment) -> Option<(f32, f32)> {
+    let d1 = self.direction();
+    let d2 = other.direction();
+    let denom = d1.cross(d2);
+
+    if denom.abs() <= EPSILON * d1.length() * d2.length() || self.is_degenerate() || other.is_degenerate() {
+      return None;
+    }
+
+    let offset = other.start - self.start;
+    Some((offset.cross(d2) / denom, offset.cross(d1) / denom))
+  }
+
+  pub fn line_intersect(&self, other: &Segment) -> Option<Vec2> {
+    self.line_intersect_params(other).map(|(t, _)| self.point_at(t))
+  }
+
+  /// Intersection of the two segments. Parameters within `EPSILON` of the
+  /// ends are accepted, so segments meeting exactly at an endpoint count.
+  /// Collinear overlapping segments report the overlap point nearest
+  /// `self.start`.
+  pub fn intersect(&self, other: &Segment) -> Option<Vec2> {
+    let in_range = |t: f32| (-EPSILON..=1.0 + EPSILON).contains(&t);
+
+    match self.line_intersect_params(other) {
+      Some((t, u)) => {
+        if in_range(t) && in_range(u) {
+          Some(self.point_at(t.clamp(0.0, 1.0)))
+        } else {
+          None
+        }
+      }
+      None => self.collinear_overlap(other),
+    }
+  }
+
+  /// Earliest parameter at which a point moving along the segment comes
+  /// within `radius` of `center`; 0 if it starts inside. Sweeping a circle
+  /// of radius `r` against a circle of radius `R` is this with `r + R`.
+  pub fn sweep_circle(&self, center: Vec2, radius: f32) -> Option<f32> {
+    let offset = self.start - center;
+    let c = offset.length_squared() - radius * radius;
+    if c <= 0.0 {
+      return Some(0.0);
+    }
+
+    let direction = self.direction();
+    let a = direction.length_squared();
+    if a <= EPSILON * EPSILON {
+      return None;
+    }
+
+    let b = 2.0 * offset.dot(direction);
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+      return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    if (0.0..=1.0).contains(&t) {
+      Some(t)
+    } else {
+      None
+    }
+  }
+
+  /// Earliest parameter at which a point moving along the segment crosses
+  /// `other`.
+  pub fn sweep_segment(&self, other: &Segment) -> Option<f32> {
+    let in_range = |t: f32| (-EPSILON..=1.0 + EPSILON).contains(&t);
+
+    match self.line_intersect_params(other) {
+      Some((t, u)) if in_range(t) && in_range(u) => Some(t.clamp(0.0, 1.0)),
+      Some(_) => None,
+      None => self.collinear_overlap(other).map(|point| self.project(point)),
+    }
+  }
+
+  /// Earliest parameter at which a point moving along the segment comes
+  /// within `radius` of `other`, i.e. enters the capsule around it.
+  pub fn sweep_capsule(&self, other: &Segment, radius: f32) -> Option<f32> {
+    if other.distance_to_point(self.start) <= radius {
+      return Some(0.0);
+    }
+
+    let mut earliest = self.sweep_circle(other.start, radius);
+    let mut consider = |t: Option<f32>| {
+      if let Some(t) = t {
+        earliest = Some(earliest.map_or(t, |e| e.min(t)));
+      }
+    };
+
+    consider(self.sweep_circle(other.end, radius));
+
+    if let Some(normal) = other.direction().perp().try_normalize() {
+      for side in [normal * radius, normal * -radius].iter() {
+        let edge = Segment::new(other.start + *side, other.end + *side);
+        consider(self.sweep_segment(&edge));
+      }
+    }
+
+    earliest
+  }
+
+  fn collinear_overlap(&self, other: &Segment) -> Option<Vec2> {
+    let tolerance = EPSILON * (1.0 + self.length().max(other.length()));
+
+    if self.is_degenerate() {
+      return if other.contains_point(self.start, tolerance) {Some(self.start)} else {None};
+    }
+    if other.is_degenerate() {
+      return if self.contains_point(other.start, tolerance) {Some(other.start)} else {None};
+    }
+
+    // Parallel but not on the same line.
+    if self.project_point(other.start).distance(other.start) > tolerance {
+      return None;
+    }
+
+    let t0 = self.project(other.start);
+    let t1 = self.project(other.end);
+    let (low, high) = if t0 < t1 {(t0, t1)} else {(t1, t0)};
+
+    let start = low.max(0.0);
+    let end = high.min(1.0);
+
+    if start <= end + EPSILON {
+      Some(self.point_at(start))
+    } else {
+      None
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+  pub min: Vec2,
+  pub max: Vec2,
+}
+
+impl Aabb {
+  pub fn new(min: Vec2, max: Vec2) -> Aabb {
+    Aabb {min, max}
+  }
+
+  pub fn from_points(a: Vec2, b: Vec2) -> Aabb {
+    Aabb {
+      min: Vec2::new(a.x.min(b.x), a.y.min(b.y)),
+      max: Vec2::new(a.x.max(b.x), a.y.max(b.y)),
+    }
+  }
+
+  pub fn around(center: Vec2, radius: f32) -> Aabb {
+    Aabb {
+      min: center - Vec2::new(radius, radius),
+      max: center + Vec2::new(radius, radius),
+    }
+  }
+
+  pub fn width(&self) -> f32 {
+    self.max.x - self.min.x
+  }
+
+  pub fn height(&self) -> f32 {
+    self.max.y - self.min.y
+  }
+
+  pub fn center(&self) -> Vec2 {
+    self.min.lerp(self.max, 0.5)
+  }
+
+  pub fn expand(&self, margin: f32) -> Aabb {
+    Aabb {
+      min: self.min - Vec2::new(margin, margin),
+      max: self.max + Vec2::new(margin, margin),
+    }
+  }
+
+  pub fn union(&self, other: &Aabb) -> Aabb {
+    Aabb {
+      min: Vec2::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+      max: Vec2::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+    }
+  }
+
+  pub fn contains(&self, point: Vec2) -> bool {
+    self.min.x <= point.x && point.x <= self.max.x &&
+    self.min.y <= point.y && point.y <= self.max.y
+  }
+
+  pub fn intersects(&self, other: &Aabb) -> bool {
+    self.min.x <= other.max.x && other.min.x <= self.max.x &&
+    self.min.y <= other.max.y && other.min.y <= self.max.y
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::f32::consts::{FRAC_PI_2, PI};
+
+  fn v(x: f32, y: f32) -> Vec2 {
+    Vec2::new(x, y)
+  }
+
+  fn seg(x0: f32, y0: f32, x1: f32, y1: f32) -> Segment {
+    Segment::new(v(x0, y0), v(x1, y1))
+  }
+
+  fn assert_close(a: Vec2, b: Vec2) {
+    assert!(a.approx_eq(b, 1e-3), "{:?} != {:?}", a, b);
+  }
+
+  #[test]
+  fn vector_arithmetic() {
+    assert_eq!(v(1.0, 2.0) + v(3.0, 4.0), v(4.0, 6.0));
+    assert_eq!(v(1.0, 2.0) - v(3.0, 4.0), v(-2.0, -2.0));
+    assert_eq!(v(1.0, 2.0) * 2.0, v(2.0, 4.0));
+    assert_eq!(v(2.0, 4.0) / 2.0, v(1.0, 2.0));
+    assert_eq!(-v(1.0, -2.0), v(-1.0, 2.0));
+
+    let mut a = v(1.0, 1.0);
+    a += v(1.0, 2.0);
+    a -= v(0.5, 0.5);
+    assert_eq!(a, v(1.5, 2.5));
+  }
+
+  #[test]
+  fn dot_and_cross() {
+    assert_eq!(v(1.0, 0.0).dot(v(0.0, 1.0)), 0.0);
+    assert_eq!(v(2.0, 3.0).dot(v(4.0, 5.0)), 23.0);
+    assert_eq!(v(1.0, 0.0).cross(v(0.0, 1.0)), 1.0);
+    assert_eq!(v(0.0, 1.0).cross(v(1.0, 0.0)), -1.0);
+    assert_eq!(v(2.0, 4.0).cross(v(1.0, 2.0)), 0.0);
+  }
+
+  #[test]
+  fn length_and_normalize() {
+    assert_eq!(v(3.0, 4.0).length(), 5.0);
+    assert_eq!(v(3.0, 4.0).length_squared(), 25.0);
+    assert_close(v(3.0, 4.0).normalize(), v(0.6, 0.8));
+    assert_eq!(Vec2::ZERO.normalize(), Vec2::ZERO);
+    assert_eq!(Vec2::ZERO.try_normalize(), None);
+    assert_eq!(v(1.0, 1.0).distance(v(4.0, 5.0)), 5.0);
+  }
+
+  #[test]
+  fn rotation() {
+    assert_close(v(1.0, 0.0).rotate(FRAC_PI_2), v(0.0, 1.0));
+    assert_close(v(1.0, 0.0).rotate(PI), v(-1.0, 0.0));
+    assert_close(v(0.0, 2.0).rotate(-FRAC_PI_2), v(2.0, 0.0));
+    assert_close(v(3.0, -1.0).perp(), v(3.0, -1.0).rotate(FRAC_PI_2));
+    assert!((v(0.0, 1.0).angle() - FRAC_PI_2).abs() < 1e-6);
+    assert_close(Vec2::from_angle(PI), v(-1.0, 0.0));
+  }
+
+  #[test]
+  fn projection_on_diagonal() {
+    let s = seg(0.0, 0.0, 10.0, 10.0);
+    assert!((s.project(v(10.0, 0.0)) - 0.5).abs() < 1e-6);
+    assert_close(s.project_point(v(10.0, 0.0)), v(5.0, 5.0));
+    assert!((s.project(v(30.0, 10.0)) - 2.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn closest_point_clamps_to_ends() {
+    let s = seg(0.0, 0.0, 10.0, 0.0);
+    assert_close(s.closest_point(v(-5.0, 3.0)), v(0.0, 0.0));
+    assert_close(s.closest_point(v(15.0, -3.0)), v(10.0, 0.0));
+    assert_close(s.closest_point(v(4.0, 7.0)), v(4.0, 0.0));
+    assert!((s.distance_to_point(v(4.0, 7.0)) - 7.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn degenerate_segment_projects_to_start() {
+    let s = seg(2.0, 2.0, 2.0, 2.0);
+    assert!(s.is_degenerate());
+    assert_eq!(s.project(v(5.0, 9.0)), 0.0);
+    assert_close(s.closest_point(v(5.0, 9.0)), v(2.0, 2.0));
+  }
+
+  #[test]
+  fn crossing_segments_intersect() {
+    let a = seg(0.0, 0.0, 10.0, 10.0);
+    let b = seg(0.0, 10.0, 10.0, 0.0);
+    assert_close(a.intersect(&b).unwrap(), v(5.0, 5.0));
+    assert_close(b.intersect(&a).unwrap(), v(5.0, 5.0));
+  }
+
+  #[test]
+  fn vertical_and_horizontal_intersect() {
+    let vertical = seg(3.0, -5.0, 3.0, 5.0);
+    let horizontal = seg(-5.0, 1.0, 5.0, 1.0);
+    assert_close(vertical.intersect(&horizontal).unwrap(), v(3.0, 1.0));
+    assert_close(horizontal.intersect(&vertical).unwrap(), v(3.0, 1.0));
+  }
+
+  #[test]
+  fn vertical_and_diagonal_intersect() {
+    let vertical = seg(2.0, -10.0, 2.0, 10.0);
+    let diagonal = seg(0.0, 0.0, 4.0, 8.0);
+    assert_close(vertical.intersect(&diagonal).unwrap(), v(2.0, 4.0));
+  }
+
+  #[test]
+  fn lines_cross_outside_segments() {
+    let a = seg(0.0, 0.0, 1.0, 1.0);
+    let b = seg(0.0, 10.0, 10.0, 0.0);
+    assert_eq!(a.intersect(&b), None);
+    assert_close(a.line_intersect(&b).unwrap(), v(5.0, 5.0));
+  }
+
+  #[test]
+  fn touching_at_endpoint_counts() {
+    let a = seg(0.0, 0.0, 5.0, 0.0);
+    let b = seg(5.0, 0.0, 5.0, 5.0);
+    assert_close(a.intersect(&b).unwrap(), v(5.0, 0.0));
+  }
+
+  #[test]
+  fn parallel_segments_do_not_intersect() {
+    let a = seg(0.0, 0.0, 10.0, 0.0);
+    let b = seg(0.0, 1.0, 10.0, 1.0);
+    assert_eq!(a.intersect(&b), None);
+    assert_eq!(a.line_intersect(&b), None);
+
+    let c = seg(0.0, 0.0, 0.0, 10.0);
+    let d = seg(1.0, 0.0, 1.0, 10.0);
+    assert_eq!(c.intersect(&d), None);
+  }
+
+  #[test]
+  fn collinear_overlap_reports_nearest_point() {
+    let a = seg(0.0, 0.0, 10.0, 0.0);
+    let b = seg(4.0, 0.0, 20.0, 0.0);
+    assert_close(a.intersect(&b).unwrap(), v(4.0, 0.0));
+    assert_close(b.intersect(&a).unwrap(), v(4.0, 0.0));
+
+    let c = seg(11.0, 0.0, 20.0, 0.0);
+    assert_eq!(a.intersect(&c), None);
+  }
+
+  #[test]
+  fn degenerate_segments_intersect_only_when_on_other() {
+    let a = seg(0.0, 0.0, 10.0, 0.0);
+    let on = seg(5.0, 0.0, 5.0, 0.0);
+    let off = seg(5.0, 1.0, 5.0, 1.0);
+    assert_close(a.intersect(&on).unwrap(), v(5.0, 0.0));
+    assert_close(on.intersect(&a).unwrap(), v(5.0, 0.0));
+    assert_eq!(a.intersect(&off), None);
+  }
+
+  #[test]
+  fn nearly_parallel_crossing_is_found() {
+    let a = seg(0.0, 0.0, 1000.0, 0.0);
+    let b = seg(0.0, -0.5, 1000.0, 0.5);
+    assert_close(a.intersect(&b).unwrap(), v(500.0, 0.0));
+  }
+
+  #[test]
+  fn sweep_circle_finds_first_contact() {
+    let motion = seg(-100.0, 0.0, 100.0, 0.0);
+    assert!((motion.sweep_circle(v(0.0, 0.0), 50.0).unwrap() - 0.25).abs() < 1e-5);
+    assert_eq!(motion.sweep_circle(v(0.0, 60.0), 50.0), None);
+    assert_eq!(motion.sweep_circle(v(-100.0, 10.0), 50.0), Some(0.0));
+    assert_eq!(motion.sweep_circle(v(300.0, 0.0), 50.0), None);
+  }
+
+  #[test]
+  fn sweep_circle_catches_tunneling() {
+    // Both endpoints are well outside the circle but the path goes through it.
+    let motion = seg(-1000.0, 3.0, 1000.0, 3.0);
+    assert!(v(-1000.0, 3.0).distance(Vec2::ZERO) > 10.0);
+    assert!(v(1000.0, 3.0).distance(Vec2::ZERO) > 10.0);
+    assert!(motion.sweep_circle(Vec2::ZERO, 10.0).is_some());
+  }
+
+  #[test]
+  fn sweep_segment_reports_parameter() {
+    let motion = seg(0.0, 0.0, 0.0, 100.0);
+    let wall = seg(-10.0, 40.0, 10.0, 40.0);
+    assert!((motion.sweep_segment(&wall).unwrap() - 0.4).abs() < 1e-5);
+    assert_eq!(motion.sweep_segment(&seg(-10.0, 140.0, 10.0, 140.0)), None);
+  }
+
+  #[test]
+  fn sweep_capsule_hits_side_and_cap() {
+    let band = seg(0.0, 0.0, 100.0, 0.0);
+
+    let from_above = seg(50.0, 100.0, 50.0, -100.0);
+    assert!((from_above.sweep_capsule(&band, 20.0).unwrap() - 0.4).abs() < 1e-4);
+
+    let into_cap = seg(-100.0, 0.0, 0.0, 0.0);
+    assert!((into_cap.sweep_capsule(&band, 20.0).unwrap() - 0.8).abs() < 1e-4);
+
+    let missing = seg(-100.0, 50.0, 200.0, 50.0);
+    assert_eq!(missing.sweep_capsule(&band, 20.0), None);
+
+    let inside = seg(50.0, 5.0, 50.0, 200.0);
+    assert_eq!(inside.sweep_capsule(&band, 20.0), Some(0.0));
+  }
+
+  #[test]
+  fn aabb_union_and_overlap() {
+    let a = Aabb::from_points(v(10.0, 0.0), v(0.0, 10.0));
+    assert_eq!(a, Aabb::new(v(0.0, 0.0), v(10.0, 10.0)));
+
+    let b = Aabb::around(v(15.0, 5.0), 2.0);
+    assert!(!a.intersects(&b));
+    assert!(a.expand(3.0).intersects(&b));
+
+    let both = a.union(&b);
+    assert_eq!(both, Aabb::new(v(0.0, 0.0), v(17.0, 10.0)));
+    assert!(both.contains(v(16.0, 4.0)));
+    assert!(!both.contains(v(16.0, 11.0)));
+  }
+
+  #[test]
+  fn point_conversions_round_trip() {
+    let p = Point {x: 1.5, y: -2.0};
+    let as_vec: Vec2 = p.into();
+    let back: Point = as_vec.into();
+    assert_eq!(back.x, p.x);
+    assert_eq!(back.y, p.y);
+
+    let m: mint::Point2<f32> = as_vec.into();
+    assert_eq!(Vec2::from(m), as_vec);
+  }
+}
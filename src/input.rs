@@ -0,0 +1,361 @@
+use ggez::event::{Button, KeyCode, MouseButton};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+  Grab,
+  Pause,
+  Restart,
+
+  Up,
+  Down,
+  Left,
+  Right,
+
+  Confirm,
+  Back,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+  Key(KeyCode),
+  Mouse(MouseButton),
+  Gamepad(Button),
+}
+
+/// Keys that can be saved as a rebinding, by their `Debug` names.
+const NAMED_KEYS: [KeyCode; 58] = [
+  KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+  KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+  KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+  KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+  KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+  KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+  KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+  KeyCode::Space, KeyCode::Return, KeyCode::Tab, KeyCode::Back, KeyCode::Escape,
+  KeyCode::LShift, KeyCode::RShift, KeyCode::LControl, KeyCode::RControl, KeyCode::LAlt, KeyCode::RAlt,
+  KeyCode::Comma, KeyCode::Period, KeyCode::Slash, KeyCode::Semicolon, KeyCode::Apostrophe,
+  KeyCode::LBracket, KeyCode::RBracket,
+];
+
+/// Name `key` is saved under, if it can be saved.
+pub fn key_name(key: KeyCode) -> Option<String> {
+  NAMED_KEYS.contains(&key).then(|| format!("{:?}", key))
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+  NAMED_KEYS.iter().copied().find(|key| format!("{:?}", key) == name)
+}
+
+pub struct InputBindings {
+  bindings: HashMap<Binding, Action>,
+}
+
+impl InputBindings {
+  pub fn new() -> InputBindings {
+    InputBindings {
+      bindings: HashMap::new(),
+    }
+  }
+
+  pub fn bind(
+    &mut self,
+    binding: Binding,
+    action: Action,
+  ) {
+    self.bindings.insert(binding, action);
+  }
+
+  pub fn unbind(
+    &mut self,
+    binding: Binding,
+  ) {
+    self.bindings.remove(&binding);
+  }
+
+  /// Drops `action`'s bindings on the same device as `binding` and binds it
+  /// to `binding` instead, so a new key leaves its mouse and gamepad
+  /// bindings alone.
+  pub fn rebind(
+    &mut self,
+    action: Action,
+    binding: Binding,
+  ) {
+    let device = std::mem::discriminant(&binding);
+    self.bindings.retain(|bound, bound_action| *bound_action != action || std::mem::discriminant(bound) != device);
+    self.bind(binding, action);
+  }
+
+  /// The default bindings with the keys from `Settings::keys` rebound on
+  /// top. Names that aren't keys are skipped.
+  pub fn with_keys(keys: &BTreeMap<Action, String>) -> InputBindings {
+    let mut bindings = InputBindings::default();
+    for (action, name) in keys {
+      if let Some(key) = key_from_name(name) {
+        bindings.rebind(*action, Binding::Key(key));
+      }
+    }
+    bindings
+  }
+
+  pub fn action_for(
+    &self,
+    binding: Binding,
+  ) -> Option<Action> {
+    self.bindings.get(&binding).copied()
+  }
+
+  pub fn bindings_for(
+    &self,
+    action: Action,
+  ) -> Vec<Binding> {
+    self.bindings
+      .iter()
+      .filter(|(_, bound_action)| **bound_action == action)
+      .map(|(binding, _)| *binding)
+      .collect()
+  }
+}
+
+impl Default for InputBindings {
+  fn default() -> InputBindings {
+    let mut bindings = InputBindings::new();
+
+    bindings.bind(Binding::Key(KeyCode::Space),         Action::Grab);
+    bindings.bind(Binding::Mouse(MouseButton::Left),    Action::Grab);
+    bindings.bind(Binding::Gamepad(Button::South),      Action::Grab);
+
+    bindings.bind(Binding::Key(KeyCode::Escape),        Action::Pause);
+    bindings.bind(Binding::Key(KeyCode::P),             Action::Pause);
+    bindings.bind(Binding::Gamepad(Button::Start),      Action::Pause);
+
+    bindings.bind(Binding::Key(KeyCode::R),             Action::Restart);
+    bindings.bind(Binding::Gamepad(Button::Select),     Action::Restart);
+
+    bindings.bind(Binding::Key(KeyCode::Up),            Action::Up);
+    bindings.bind(Binding::Key(KeyCode::Down),          Action::Down);
+    bindings.bind(Binding::Key(KeyCode::Left),          Action::Left);
+    bindings.bind(Binding::Key(KeyCode::Right),         Action::Right);
+    bindings.bind(Binding::Gamepad(Button::DPadUp),     Action::Up);
+    bindings.bind(Binding::Gamepad(Button::DPadDown),   Action::Down);
+    bindings.bind(Binding::Gamepad(Button::DPadLeft),   Action::Left);
+    bindings.bind(Binding::Gamepad(Button::DPadRight),  Action::Right);
+
+    bindings.bind(Binding::Key(KeyCode::Return),        Action::Confirm);
+    bindings.bind(Binding::Gamepad(Button::East),       Action::Back);
+    bindings.bind(Binding::Key(KeyCode::Back),          Action::Back);
+
+    bindings
+  }
+}
+
+/// Actions collected over a single tick. `pressed` and `released` only hold
+/// for the tick the event arrived in. An action is held while any binding
+/// for it is down, so letting go of one of two keys doesn't release it.
+#[derive(Clone)]
+pub struct InputState {
+  /// Bindings that are down, with the action each one pressed.
+  down: HashMap<Binding, Action>,
+  pressed: HashSet<Action>,
+  released: HashSet<Action>,
+  pub mouse_pos: mint::Point2<f32>,
+}
+
+impl InputState {
+  pub fn is_held(&self, action: Action) -> bool {
+    self.down.values().any(|down_action| *down_action == action)
+  }
+
+  pub fn was_pressed(&self, action: Action) -> bool {
+    self.pressed.contains(&action)
+  }
+
+  pub fn was_released(&self, action: Action) -> bool {
+    self.released.contains(&action)
+  }
+
+  pub fn press(
+    &mut self,
+    binding: Binding,
+    action: Action,
+  ) {
+    let was_held = self.is_held(action);
+    self.down.insert(binding, action);
+    if !was_held {
+      self.pressed.insert(action);
+    }
+  }
+
+  /// Releases whatever `binding` pressed, even if it has been rebound since.
+  pub fn release(
+    &mut self,
+    binding: Binding,
+  ) {
+    if let Some(action) = self.down.remove(&binding) {
+      if !self.is_held(action) {
+        self.released.insert(action);
+      }
+    }
+  }
+
+  pub fn without_edges(&self) -> InputState {
+    InputState {
+      down: self.down.clone(),
+      pressed: HashSet::new(),
+      released: HashSet::new(),
+      mouse_pos: self.mouse_pos,
+    }
+  }
+
+  /// Clears the per-tick edges once the active state has consumed them.
+  pub fn end_tick(&mut self) {
+    self.pressed.clear();
+    self.released.clear();
+  }
+
+  /// Drops everything, e.g. when the window loses focus and releases
+  /// would otherwise never arrive.
+  pub fn clear(&mut self) {
+    self.down.clear();
+    self.pressed.clear();
+    self.released.clear();
+  }
+}
+
+impl Default for InputState {
+  fn default() -> InputState {
+    InputState {
+      down: HashMap::new(),
+      pressed: HashSet::new(),
+      released: HashSet::new(),
+      mouse_pos: mint::Point2 {x: 0.0, y: 0.0},
+    }
+  }
+}
+
+pub struct Input {
+  pub bindings: InputBindings,
+  pub state: InputState,
+}
+
+impl Input {
+  pub fn new(bindings: InputBindings) -> Input {
+    Input {
+      bindings,
+      state: InputState::default(),
+    }
+  }
+
+  pub fn binding_down(
+    &mut self,
+    binding: Binding,
+  ) {
+    if let Some(action) = self.bindings.action_for(binding) {
+      self.state.press(binding, action);
+    }
+  }
+
+  pub fn binding_up(
+    &mut self,
+    binding: Binding,
+  ) {
+    self.state.release(binding);
+  }
+}
+
+impl Default for Input {
+  fn default() -> Input {
+    Input::new(InputBindings::default())
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const UP: Binding = Binding::Key(KeyCode::Up);
+  const W: Binding = Binding::Key(KeyCode::W);
+
+  #[test]
+  fn edges_last_one_tick_and_held_lasts_until_release() {
+    let mut state = InputState::default();
+    state.press(UP, Action::Up);
+    assert!(state.was_pressed(Action::Up) && state.is_held(Action::Up));
+
+    state.end_tick();
+    assert!(!state.was_pressed(Action::Up) && state.is_held(Action::Up));
+
+    state.release(UP);
+    assert!(state.was_released(Action::Up) && !state.is_held(Action::Up));
+
+    state.end_tick();
+    assert!(!state.was_released(Action::Up));
+  }
+
+  #[test]
+  fn action_stays_held_while_any_binding_is_down() {
+    let mut state = InputState::default();
+    state.press(UP, Action::Up);
+    state.end_tick();
+
+    // A second key for a held action is no new press.
+    state.press(W, Action::Up);
+    assert!(!state.was_pressed(Action::Up));
+
+    state.release(UP);
+    assert!(state.is_held(Action::Up));
+    assert!(!state.was_released(Action::Up));
+
+    state.release(W);
+    assert!(!state.is_held(Action::Up));
+    assert!(state.was_released(Action::Up));
+  }
+
+  #[test]
+  fn release_follows_the_action_pressed_even_after_a_rebind() {
+    let mut input = Input::default();
+    input.binding_down(Binding::Key(KeyCode::Space));
+    input.bindings.rebind(Action::Pause, Binding::Key(KeyCode::Space));
+
+    input.binding_up(Binding::Key(KeyCode::Space));
+    assert!(input.state.was_released(Action::Grab));
+    assert!(!input.state.is_held(Action::Grab));
+  }
+
+  #[test]
+  fn rebind_replaces_keys_but_keeps_other_devices() {
+    let mut bindings = InputBindings::default();
+    bindings.rebind(Action::Grab, Binding::Key(KeyCode::G));
+
+    assert_eq!(bindings.action_for(Binding::Key(KeyCode::G)), Some(Action::Grab));
+    assert_eq!(bindings.action_for(Binding::Key(KeyCode::Space)), None);
+    assert_eq!(bindings.action_for(Binding::Mouse(MouseButton::Left)), Some(Action::Grab));
+    assert_eq!(bindings.action_for(Binding::Gamepad(Button::South)), Some(Action::Grab));
+  }
+
+  #[test]
+  fn unbind_drops_only_that_binding() {
+    let mut bindings = InputBindings::default();
+    bindings.unbind(Binding::Key(KeyCode::P));
+
+    assert_eq!(bindings.action_for(Binding::Key(KeyCode::P)), None);
+    assert_eq!(bindings.action_for(Binding::Key(KeyCode::Escape)), Some(Action::Pause));
+  }
+
+  #[test]
+  fn saved_keys_are_rebound_by_name() {
+    let keys: BTreeMap<Action, String> = vec![(Action::Restart, "T".to_string()), (Action::Grab, "NotAKey".to_string())].into_iter().collect();
+    let bindings = InputBindings::with_keys(&keys);
+
+    assert_eq!(bindings.action_for(Binding::Key(KeyCode::T)), Some(Action::Restart));
+    assert_eq!(bindings.action_for(Binding::Gamepad(Button::Select)), Some(Action::Restart));
+    assert_eq!(bindings.action_for(Binding::Key(KeyCode::R)), None);
+    assert_eq!(bindings.action_for(Binding::Key(KeyCode::Space)), Some(Action::Grab));
+    assert_eq!(key_from_name(&key_name(KeyCode::LShift).unwrap()), Some(KeyCode::LShift));
+  }
+}
@@ -2,6 +2,14 @@ pub mod states;
 pub mod game_objects;
 
 pub mod screen_context;
+pub mod geometry;
+pub mod input;
+pub mod clock;
+pub mod progress;
+pub mod score;
+pub mod settings;
+pub mod particles;
+pub mod debug_overlay;
 pub mod drawing_helpers;
 
 use std::path::PathBuf;
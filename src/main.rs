@@ -1,13 +1,24 @@
 
-use ggez::event::{self, EventHandler, KeyCode, KeyMods};
+use ggez::event::{self, EventHandler, Button, GamepadId, KeyCode, KeyMods, MouseButton};
 use ggez::{graphics, timer, conf};
 use ggez::{Context, GameResult};
-use std::time::{SystemTime, UNIX_EPOCH};
 
-use skyship_rust::{game_objects, screen_context, states, get_resource_folder};
+use skyship_rust::{game_objects, screen_context, states, input, clock, progress, settings, drawing_helpers, debug_overlay};
 use game_objects::level::Level;
-use states::State;
+use states::StateMachine;
+use states::start::Start;
+use states::cnfrm::Cnfrm;
 use screen_context::ScreenContext;
+use input::{Action, Binding, Input, InputBindings};
+use clock::GameClock;
+use progress::Progress;
+use settings::{Args, DisplayMode, Settings, USAGE};
+use drawing_helpers::starfield::Starfield;
+use debug_overlay::DebugOverlay;
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::path::PathBuf;
 
 
 #[cfg(target_os = "macos")]
@@ -15,33 +26,117 @@ static MODIFIER_KEY: KeyMods = KeyMods::LOGO;
 #[cfg(not(target_os = "macos"))]
 static MODIFIER_KEY: KeyMods = KeyMods::CTRL;
 
-static SHOW_FRAMERATE: bool = true;
-
 
 struct MainState {
-    state: State,
-    state_start: u64,
+    states: StateMachine,
     level: Level,
     screen_ctx: ScreenContext,
-    frame_time: (f64, u64),
-    frame_rate: String
+    starfield: Starfield,
+    input: Input,
+    clock: GameClock,
+    /// Whether losing focus is what paused the clock, so regaining it
+    /// doesn't undo a debug pause.
+    paused_by_focus: bool,
+    progress: Progress,
+    progress_path: PathBuf,
+    settings: Settings,
+    settings_path: PathBuf,
+    /// Display mode the window is actually in. Starts from the config, so
+    /// `--windowed` holds until the player picks a mode.
+    display_mode: DisplayMode,
+    /// `settings.display_mode` as last seen, so a change from the settings
+    /// menu can be picked up.
+    seen_display_mode: DisplayMode,
+    /// `settings.keys` as last applied to the bindings.
+    seen_keys: BTreeMap<Action, String>,
+    /// Whether a settings menu was open last update, to save when it closes.
+    editing_settings: bool,
+    debug: DebugOverlay,
+    /// Set once progress and settings are saved on the way out, so waiting
+    /// for ggez to exit doesn't save again.
+    shutting_down: bool,
 }
 
 impl MainState {
-    fn new() -> MainState {
-        let mut s = MainState {
-            state: State::Start,
-            state_start: get_current_ms(),
-            level: Level {
-                attractors: Vec::new(),
-                zippers: Vec::new(),
-            },
-            screen_ctx: ScreenContext::default(),
-            frame_time: (1000.0 / 60.0, get_current_ms()),
-            frame_rate: "".to_string()
-        };
-        s.level.load_level(0);
-        s
+    /// `settings` is what gets saved back; `config` is the same with the
+    /// command line flags applied.
+    fn new(mut level: Level, progress: Progress, settings: Settings, config: &Settings) -> MainState {
+        let start_level = level.number;
+        let mut screen_ctx = ScreenContext::default();
+        let clock = GameClock::new();
+
+        MainState {
+            states: StateMachine::new(Box::new(Start::default()), &mut screen_ctx, &mut level, clock.millis()),
+            level,
+            screen_ctx,
+            starfield: Starfield::new(start_level as u64),
+            input: Input::new(InputBindings::with_keys(&settings.keys)),
+            clock,
+            paused_by_focus: false,
+            progress,
+            progress_path: data_dir().join("progress.json"),
+            display_mode: config.display_mode,
+            seen_display_mode: settings.display_mode,
+            seen_keys: settings.keys.clone(),
+            editing_settings: false,
+            settings,
+            settings_path: data_dir().join("settings.json"),
+            debug: DebugOverlay::new(),
+            shutting_down: false,
+        }
+    }
+
+    /// Returns whether the game should exit now. Quitting mid-run asks for
+    /// confirmation first instead, and asking again while the question is
+    /// up does nothing.
+    fn request_quit(&mut self) -> bool {
+        if self.states.asks_to_quit() {
+            false
+        } else if self.states.in_run() {
+            self.states.push(Box::new(Cnfrm::default()), &mut self.screen_ctx, &mut self.level, self.clock.millis());
+            false
+        } else {
+            self.shutdown();
+            true
+        }
+    }
+
+    /// If the platform refuses the mode, logs why and stays in the current
+    /// one, putting the settings back to match.
+    fn set_display_mode(&mut self, ctx: &mut Context, display_mode: DisplayMode) {
+        match graphics::set_fullscreen(ctx, display_mode.fullscreen_type()) {
+            Ok(()) => self.display_mode = display_mode,
+            Err(e) => eprintln!("Failed to switch to {} display mode: {}", display_mode.name(), e),
+        }
+        self.settings.display_mode = self.display_mode;
+        self.seen_display_mode = self.display_mode;
+
+        if self.display_mode == DisplayMode::Windowed {
+            if let Err(e) = graphics::set_drawable_size(ctx, self.settings.window_width, self.settings.window_height) {
+                eprintln!("Failed to resize window: {}", e);
+            }
+        }
+        if let Err(e) = fit_screen_coordinates(ctx) {
+            eprintln!("Failed to fit screen coordinates: {}", e);
+        }
+    }
+
+    fn shutdown(&mut self) {
+        if self.shutting_down {
+            return;
+        }
+        self.shutting_down = true;
+
+        if let Err(e) = self.progress.save(&self.progress_path) {
+            eprintln!("Failed to save progress to {}: {}", self.progress_path.display(), e);
+        }
+        self.save_settings();
+    }
+
+    fn save_settings(&self) {
+        if let Err(e) = self.settings.save(&self.settings_path) {
+            eprintln!("Failed to save settings to {}: {}", self.settings_path.display(), e);
+        }
     }
 }
 
@@ -51,20 +146,41 @@ impl EventHandler for MainState {
         let graphics::Rect {x: _, y: _, w: width, h: height} = graphics::screen_coordinates(ctx);
         
         self.screen_ctx.size = (width, height);
-        let lerp_amount = 0.05;
-        self.frame_time.0 = self.frame_time.0 * (1.0 - lerp_amount) + (get_current_ms() - self.frame_time.1) as f64 * lerp_amount;
-        self.frame_time.1 = get_current_ms();
-        
-        self.frame_rate = format!("{}", (1000.0 / self.frame_time.0 + 0.5) as u64);
+        self.screen_ctx.dpi = graphics::window(ctx).scale_factor() as f32;
+        let real_delta = timer::delta(ctx);
+        self.clock.advance(real_delta);
 
-        
-        match self.state.update(&mut self.screen_ctx, &mut self.level, get_current_ms() - self.state_start) {
-            Some(s) => {
-                self.state = s;
-                self.state_start = get_current_ms();
-                self.update(ctx)?;
-            }
-            _ => (),
+        self.debug.record_frame(real_delta.as_secs_f32() * 1000.0);
+
+        self.states.update(&mut self.screen_ctx, &mut self.level, &mut self.settings, &self.input.state, self.clock.millis());
+        self.input.state.end_tick();
+
+        self.progress.current_level = self.level.number;
+        if self.starfield.seed() != self.level.number as u64 {
+            self.starfield = Starfield::new(self.level.number as u64);
+        }
+        if self.states.level_complete() {
+            self.progress.complete(self.level.number);
+        }
+
+        if self.settings.display_mode != self.seen_display_mode {
+            self.set_display_mode(ctx, self.settings.display_mode);
+        }
+
+        let editing_settings = self.states.edits_settings();
+        if self.editing_settings && !editing_settings {
+            self.save_settings();
+        }
+        self.editing_settings = editing_settings;
+
+        if self.settings.keys != self.seen_keys {
+            self.input.bindings = InputBindings::with_keys(&self.settings.keys);
+            self.seen_keys = self.settings.keys.clone();
+        }
+
+        if self.states.quit_requested() && !self.shutting_down {
+            self.shutdown();
+            event::quit(ctx);
         }
 
         Ok(())
@@ -72,15 +188,12 @@ impl EventHandler for MainState {
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         graphics::clear(ctx, [0.0, 0.0, 0.0, 1.0].into());
+        self.starfield.draw(ctx, &self.screen_ctx)?;
 
-        self.state.draw(ctx, &self.screen_ctx, &self.level, get_current_ms() - self.state_start)?;
+        self.states.draw(ctx, &self.screen_ctx, &self.level, self.clock.millis())?;
 
-        if SHOW_FRAMERATE {
-            graphics::draw(
-                ctx,
-                &graphics::Text::new("Framerate: ".to_string() + self.frame_rate.as_str()),
-                graphics::DrawParam::default()
-            )?;
+        if self.settings.show_debug {
+            self.debug.draw(ctx, &self.screen_ctx, &self.level, &self.states, &self.clock)?;
         }
 
         graphics::present(ctx)?;
@@ -91,40 +204,171 @@ impl EventHandler for MainState {
     fn key_down_event(&mut self, ctx: &mut Context, key: KeyCode, mods: KeyMods, _: bool) {
         match key {
             // Quit if MODIFIER_KEY+Q is pressed.
-            KeyCode::Q => {
-                if mods == MODIFIER_KEY {
+            KeyCode::Q if mods == MODIFIER_KEY => {
+                if self.request_quit() {
                     event::quit(ctx);
                 }
+                return;
             }
-            KeyCode::W => {
-                if mods == MODIFIER_KEY | KeyMods::SHIFT {
+            KeyCode::W if mods == MODIFIER_KEY | KeyMods::SHIFT => {
+                if self.request_quit() {
                     event::quit(ctx);
                 }
+                return;
+            }
+            KeyCode::Return if mods == KeyMods::ALT => {
+                let next = self.display_mode.next();
+                self.set_display_mode(ctx, next);
+                return;
+            }
+            KeyCode::F11 => {
+                let next = self.display_mode.next();
+                self.set_display_mode(ctx, next);
+                return;
+            }
+            KeyCode::F3 => {
+                self.settings.show_debug = !self.settings.show_debug;
+                return;
+            }
+            // Clock controls, only while the debug overlay is up.
+            KeyCode::F6 if self.settings.show_debug => {
+                if self.clock.is_paused() {
+                    self.clock.resume();
+                } else {
+                    self.clock.pause();
+                }
+                return;
+            }
+            KeyCode::F7 if self.settings.show_debug => {
+                self.clock.step();
+                return;
+            }
+            KeyCode::F8 if self.settings.show_debug => {
+                self.clock.cycle_time_scale();
+                return;
             }
             _ => (),
         }
+
+        self.input.binding_down(Binding::Key(key));
+        self.states.key_down_event(&mut self.screen_ctx, &mut self.level, key, mods, self.clock.millis());
+    }
+
+    fn key_up_event(&mut self, _ctx: &mut Context, key: KeyCode, mods: KeyMods) {
+        self.input.binding_up(Binding::Key(key));
+        self.states.key_up_event(&mut self.screen_ctx, &mut self.level, key, mods, self.clock.millis());
+    }
+
+    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        self.input.binding_down(Binding::Mouse(button));
+        self.states.mouse_button_down_event(&mut self.screen_ctx, &mut self.level, button, mint::Point2 {x, y}, self.clock.millis());
+    }
+
+    fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        self.input.binding_up(Binding::Mouse(button));
+        self.states.mouse_button_up_event(&mut self.screen_ctx, &mut self.level, button, mint::Point2 {x, y}, self.clock.millis());
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.input.state.mouse_pos = mint::Point2 {x, y};
+    }
+
+    fn gamepad_button_down_event(&mut self, _ctx: &mut Context, btn: Button, _id: GamepadId) {
+        self.input.binding_down(Binding::Gamepad(btn));
+    }
+
+    fn gamepad_button_up_event(&mut self, _ctx: &mut Context, btn: Button, _id: GamepadId) {
+        self.input.binding_up(Binding::Gamepad(btn));
+    }
+
+    fn focus_event(&mut self, _ctx: &mut Context, gained: bool) {
+        if gained {
+            if self.paused_by_focus {
+                self.clock.resume();
+                self.paused_by_focus = false;
+            }
+        } else {
+            self.input.state.clear();
+            if !self.clock.is_paused() {
+                self.clock.pause();
+                self.paused_by_focus = true;
+            }
+        }
     }
 
     fn quit_event(&mut self, _: &mut Context) -> bool {
-        let (ctx, event_loop) = ggez::ContextBuilder::new("SkyShip", "Sky C").build().unwrap();
-        event::run(ctx, event_loop, MainState::new())
+        !self.request_quit()
     }
 
     fn resize_event(&mut self, ctx: &mut Context, _width: f32, _height: f32) {
-        let winit::dpi::LogicalSize {width, height} = graphics::window(ctx).inner_size().to_logical(graphics::window(ctx).scale_factor());
-        graphics::set_screen_coordinates(ctx, graphics::Rect { x: 0.0, y: 0.0, w: width, h: height }).unwrap();
+        fit_screen_coordinates(ctx).unwrap();
+
+        if self.display_mode == DisplayMode::Windowed {
+            let graphics::Rect {x: _, y: _, w: width, h: height} = graphics::screen_coordinates(ctx);
+            self.settings.window_width = width;
+            self.settings.window_height = height;
+        }
+    }
+}
+
+/// Maps screen coordinates one-to-one onto the window's logical pixels.
+fn fit_screen_coordinates(ctx: &mut Context) -> GameResult {
+    let winit::dpi::LogicalSize {width, height} = graphics::window(ctx).inner_size().to_logical(graphics::window(ctx).scale_factor());
+    graphics::set_screen_coordinates(ctx, graphics::Rect { x: 0.0, y: 0.0, w: width, h: height })
+}
+
+/// ggez's user data directory for this game, where settings and progress
+/// are saved. Found without a `Context` because the window can't be built
+/// until the settings are read.
+fn data_dir() -> PathBuf {
+    directories::ProjectDirs::from("", "Sky C", "SkyShip")
+        .map(|dirs| dirs.data_local_dir().to_path_buf())
+        .unwrap_or_default()
+}
+
+/// The level to open on: the one asked for, or else the last one played. A
+/// saved level the level file no longer has falls back to the first.
+fn load_start_level(config: &Settings, progress: &Progress) -> Result<Level, String> {
+    let load = |level_ind| {
+        let mut level = Level::default();
+        match config.levels_path.as_ref() {
+            Some(path) => level.load_level_from(path, level_ind),
+            None => level.load_level(level_ind),
+        }.map(|()| level)
+    };
+
+    match config.start_level {
+        Some(level_ind) => load(level_ind),
+        None => load(progress.current_level).or_else(|_| load(0)),
     }
 }
 
 pub fn main() -> ggez::GameResult {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
 
-    println!("{}", get_resource_folder());
+    let settings = Settings::load(&data_dir().join("settings.json"));
+    let config = settings.with_args(&args);
+    let progress = Progress::load(&data_dir().join("progress.json"));
+
+    let level = match load_start_level(&config, &progress) {
+        Ok(level) => level,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
 
     let mut cb = ggez::ContextBuilder::new("SkyShip", "Sky C");
     cb = cb.window_setup(conf::WindowSetup {
         title: "SkyShip".to_owned(),
-        samples: conf::NumSamples::Sixteen,
-        vsync: true,
+        samples: conf::NumSamples::try_from(config.msaa).unwrap_or(conf::NumSamples::One),
+        vsync: config.vsync,
         icon: "".to_owned(),
         srgb: true,
     });
@@ -134,8 +378,8 @@ pub fn main() -> ggez::GameResult {
         &mut ctx, 
         conf::WindowMode {
 
-            width: 800.0,
-            height: 600.0,
+            width: config.window_width,
+            height: config.window_height,
 
             min_width: 400.0,
             min_height: 300.0,
@@ -148,19 +392,10 @@ pub fn main() -> ggez::GameResult {
             borderless: false,
 
             maximized: false,
-            fullscreen_type: conf::FullscreenType::Windowed,
+            fullscreen_type: config.display_mode.fullscreen_type(),
         }
     )?;
 
-    let state = MainState::new();
-    println!("{:?}", ctx.continuing);
+    let state = MainState::new(level, progress, settings, &config);
     event::run(ctx, event_loop, state)
-}
-
-fn get_current_ms() -> u64 {
-    let start = SystemTime::now();
-    let since_the_epoch = start
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards");
-    since_the_epoch.as_secs() * 1000 + since_the_epoch.subsec_nanos() as u64 / 1_000_000
 }
\ No newline at end of file
@@ -0,0 +1,337 @@
+use ggez::{graphics};
+use ggez::{Context, GameResult};
+
+use super::geometry::Vec2;
+use super::screen_context::ScreenContext;
+
+/// Most particles alive at once; emitting into a full pool recycles the
+/// oldest.
+pub const DEFAULT_CAPACITY: usize = 2048;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Particle {
+  pos: Vec2,
+  vel: Vec2,
+  age: f32,
+  lifetime: f32,
+  style: ParticleStyle,
+}
+
+/// How a particle looks over its life, interpolated from start to end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleStyle {
+  pub start_color: [f32; 4],
+  pub end_color: [f32; 4],
+  pub start_size: f32,
+  pub end_size: f32,
+  /// Fraction of velocity kept per second.
+  pub drag: f32,
+}
+
+/// One burst of particles: `count` of them from `pos`, heading within
+/// `spread` radians either side of `direction` at `speed` units per ms,
+/// each living `lifetime` ms. Speeds and lifetimes get `jitter` (0 to 1)
+/// of random variation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Emitter {
+  pub count: usize,
+  pub direction: f32,
+  pub spread: f32,
+  pub speed: f32,
+  pub lifetime: f32,
+  pub jitter: f32,
+  pub style: ParticleStyle,
+}
+
+impl Emitter {
+  /// Faint sparks left behind a moving ship.
+  pub fn ship_trail(heading: Vec2) -> Emitter {
+    Emitter {
+      count: 1,
+      direction: (-heading).angle(),
+      spread: 0.4,
+      speed: 0.03,
+      lifetime: 400.0,
+      jitter: 0.5,
+      style: ParticleStyle {
+        start_color: [0.6, 0.8, 1.0, 0.6],
+        end_color: [0.2, 0.3, 1.0, 0.0],
+        start_size: 3.0,
+        end_size: 1.0,
+        drag: 0.2,
+      },
+    }
+  }
+
+  /// Sparks thrown forward along a zipper's direction while it boosts.
+  pub fn zipper_boost(direction: Vec2) -> Emitter {
+    Emitter {
+      count: 2,
+      direction: direction.angle(),
+      spread: 0.25,
+      speed: 0.25,
+      lifetime: 350.0,
+      jitter: 0.6,
+      style: ParticleStyle {
+        start_color: [1.0, 1.0, 0.5, 1.0],
+        end_color: [1.0, 0.5, 0.0, 0.0],
+        start_size: 2.5,
+        end_size: 0.5,
+        drag: 0.05,
+      },
+    }
+  }
+
+  /// An even ring spreading out from the ship when an attractor catches it.
+  pub fn capture_ring() -> Emitter {
+    Emitter {
+      count: 36,
+      direction: 0.0,
+      spread: std::f32::consts::PI,
+      speed: 0.12,
+      lifetime: 500.0,
+      jitter: 0.0,
+      style: ParticleStyle {
+        start_color: [0.3, 1.0, 0.3, 0.9],
+        end_color: [0.3, 1.0, 0.3, 0.0],
+        start_size: 3.0,
+        end_size: 1.5,
+        drag: 0.1,
+      },
+    }
+  }
+
+  pub fn explosion() -> Emitter {
+    Emitter {
+      count: 120,
+      direction: 0.0,
+      spread: std::f32::consts::PI,
+      speed: 0.25,
+      lifetime: 900.0,
+      jitter: 0.8,
+      style: ParticleStyle {
+        start_color: [1.0, 0.9, 0.4, 1.0],
+        end_color: [0.8, 0.1, 0.0, 0.0],
+        start_size: 5.0,
+        end_size: 1.0,
+        drag: 0.05,
+      },
+    }
+  }
+}
+
+/// Fixed-size pool of particles, all drawn as a single mesh. Uses its own
+/// seeded generator, reset at the start of every attempt, so effects come
+/// out the same on every replay.
+pub struct ParticleSystem {
+  particles: Vec<Particle>,
+  capacity: usize,
+  next_recycled: usize,
+  rng: u32,
+}
+
+impl ParticleSystem {
+  pub fn new(capacity: usize) -> ParticleSystem {
+    ParticleSystem {
+      particles: Vec::with_capacity(capacity),
+      capacity,
+      next_recycled: 0,
+      rng: seed_rng(0, 0),
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.particles.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.particles.is_empty()
+  }
+
+  pub fn clear(&mut self) {
+    self.particles.clear();
+    self.next_recycled = 0;
+  }
+
+  /// Clears the pool for an attempt at `level` starting `ticks` into the
+  /// run, and reseeds the generator from them.
+  pub fn reset(
+    &mut self,
+    level: usize,
+    ticks: u64,
+  ) {
+    self.clear();
+    self.rng = seed_rng(level, ticks);
+  }
+
+  pub fn emit(
+    &mut self,
+    emitter: &Emitter,
+    pos: Vec2,
+  ) {
+    for i in 0..emitter.count {
+      // Full circles are spaced evenly; narrower cones are random.
+      let offset = if emitter.spread >= std::f32::consts::PI {
+        i as f32 / emitter.count as f32 * std::f32::consts::TAU
+      } else {
+        (self.next_random() * 2.0 - 1.0) * emitter.spread
+      };
+      let speed = emitter.speed * (1.0 - emitter.jitter * self.next_random());
+      let lifetime = emitter.lifetime * (1.0 - emitter.jitter * 0.5 * self.next_random());
+
+      self.spawn(Particle {
+        pos,
+        vel: Vec2::from_angle(emitter.direction + offset) * speed,
+        age: 0.0,
+        lifetime,
+        style: emitter.style,
+      });
+    }
+  }
+
+  fn spawn(
+    &mut self,
+    particle: Particle,
+  ) {
+    if self.particles.len() < self.capacity {
+      self.particles.push(particle);
+    } else if self.capacity > 0 {
+      self.particles[self.next_recycled] = particle;
+      self.next_recycled = (self.next_recycled + 1) % self.capacity;
+    }
+  }
+
+  /// Ages and moves every particle by `dt` ms, dropping the expired ones.
+  pub fn update(
+    &mut self,
+    dt: f32,
+  ) {
+    for particle in self.particles.iter_mut() {
+      particle.age += dt;
+      particle.vel = particle.vel * particle.style.drag.powf(dt / 1000.0);
+      particle.pos += particle.vel * dt;
+    }
+    self.particles.retain(|particle| particle.age < particle.lifetime);
+    self.next_recycled = self.next_recycled.min(self.particles.len().saturating_sub(1));
+  }
+
+  pub fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    if self.particles.is_empty() {
+      return Ok(());
+    }
+
+    let mut mesh = graphics::MeshBuilder::new();
+    for particle in self.particles.iter() {
+      let t = (particle.age / particle.lifetime).min(1.0);
+      let style = &particle.style;
+
+      let mut color = [0.0; 4];
+      for (channel, (start, end)) in color.iter_mut().zip(style.start_color.iter().zip(style.end_color.iter())) {
+        *channel = start + (end - start) * t;
+      }
+      let size = style.start_size + (style.end_size - style.start_size) * t;
+
+      mesh.circle(
+        graphics::DrawMode::fill(),
+        screen.point_game_to_screen(particle.pos.into()),
+        screen.size_game_to_screen(size).max(0.5),
+        0.5,
+        color.into()
+      )?;
+    }
+
+    let mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+
+  /// Uniform in [0, 1), from a xorshift generator.
+  fn next_random(&mut self) -> f32 {
+    self.rng ^= self.rng << 13;
+    self.rng ^= self.rng >> 17;
+    self.rng ^= self.rng << 5;
+    (self.rng >> 8) as f32 / (1u32 << 24) as f32
+  }
+}
+
+/// Never zero, which would stall the xorshift generator.
+fn seed_rng(
+  level: usize,
+  ticks: u64,
+) -> u32 {
+  let seed = (level as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ ticks.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+  ((seed >> 32) as u32 ^ seed as u32) | 1
+}
+
+impl Default for ParticleSystem {
+  fn default() -> ParticleSystem {
+    ParticleSystem::new(DEFAULT_CAPACITY)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn particles_expire_after_their_lifetime() {
+    let mut particles = ParticleSystem::default();
+    particles.emit(&Emitter::capture_ring(), Vec2::ZERO);
+    assert_eq!(particles.len(), 36);
+
+    particles.update(499.0);
+    assert_eq!(particles.len(), 36);
+    particles.update(2.0);
+    assert!(particles.is_empty());
+  }
+
+  #[test]
+  fn full_pool_recycles_instead_of_growing() {
+    let mut particles = ParticleSystem::new(50);
+    particles.emit(&Emitter::explosion(), Vec2::ZERO);
+    assert_eq!(particles.len(), 50);
+  }
+
+  #[test]
+  fn ring_spreads_evenly() {
+    let mut particles = ParticleSystem::default();
+    particles.emit(&Emitter::capture_ring(), Vec2::ZERO);
+    particles.update(100.0);
+
+    let distances: Vec<f32> = particles.particles.iter().map(|particle| particle.pos.length()).collect();
+    assert!(distances.iter().all(|d| (d - distances[0]).abs() < 1e-3));
+  }
+
+  #[test]
+  fn emission_is_deterministic() {
+    let run = || {
+      let mut particles = ParticleSystem::default();
+      particles.emit(&Emitter::explosion(), Vec2::new(5.0, 5.0));
+      particles.update(16.0);
+      particles.particles.clone()
+    };
+    assert_eq!(run(), run());
+  }
+
+  #[test]
+  fn reset_replays_the_same_effects() {
+    let mut particles = ParticleSystem::default();
+    let burst = |particles: &mut ParticleSystem| {
+      particles.reset(2, 600);
+      particles.emit(&Emitter::zipper_boost(Vec2::new(1.0, 0.0)), Vec2::ZERO);
+      particles.update(16.0);
+      particles.particles.clone()
+    };
+
+    let first = burst(&mut particles);
+    assert_eq!(burst(&mut particles), first);
+
+    particles.reset(3, 600);
+    particles.emit(&Emitter::zipper_boost(Vec2::new(1.0, 0.0)), Vec2::ZERO);
+    particles.update(16.0);
+    assert_ne!(particles.particles, first);
+  }
+}
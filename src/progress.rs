@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct Progress {
+  /// Level the game opens on next launch: the last one played.
+  pub current_level: usize,
+  /// Every level up to this one has been reached. One past the last level
+  /// once the game is finished.
+  pub highest_unlocked: usize,
+}
+
+impl Progress {
+  /// Records a win on `level`, unlocking the one after it.
+  pub fn complete(
+    &mut self,
+    level: usize,
+  ) {
+    self.highest_unlocked = self.highest_unlocked.max(level + 1);
+  }
+
+  /// Falls back to a fresh save if the file is missing or unreadable.
+  pub fn load(path: &Path) -> Progress {
+    std::fs::read_to_string(path)
+      .ok()
+      .and_then(|file_string| serde_json::from_str(&file_string).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn save(
+    &self,
+    path: &Path,
+  ) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+      std::fs::create_dir_all(dir)?;
+    }
+
+    let json_string = serde_json::to_string(self)
+      .map_err(std::io::Error::other)?;
+
+    std::fs::write(path, json_string)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn completing_unlocks_the_next_level_once() {
+    let mut progress = Progress::default();
+    progress.complete(0);
+    assert_eq!(progress.highest_unlocked, 1);
+
+    progress.complete(3);
+    progress.complete(1);
+    assert_eq!(progress.highest_unlocked, 4);
+  }
+}
@@ -0,0 +1,51 @@
+/// Runs finished within this many milliseconds earn a time bonus.
+pub const PAR_MILLIS: u64 = 60_000;
+/// Bonus points per second left under par.
+pub const POINTS_PER_SECOND: u64 = 10;
+/// Points lost for every death or restart before the winning attempt.
+pub const DEATH_PENALTY: u64 = 250;
+
+/// Result of a won run: pickups and time come from the winning attempt,
+/// deaths from every attempt before it.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Score {
+  pub pickups: u32,
+  pub millis: u64,
+  pub deaths: u32,
+  /// Aim assist was on at some point; the run doesn't count for ranked times.
+  pub assisted: bool,
+}
+
+impl Score {
+  pub fn time_bonus(&self) -> u64 {
+    PAR_MILLIS.saturating_sub(self.millis) * POINTS_PER_SECOND / 1000
+  }
+
+  pub fn death_penalty(&self) -> u64 {
+    self.deaths as u64 * DEATH_PENALTY
+  }
+
+  pub fn total(&self) -> u64 {
+    (self.pickups as u64 + self.time_bonus()).saturating_sub(self.death_penalty())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn total_combines_pickups_time_and_deaths() {
+    let score = Score {pickups: 300, millis: 20_000, deaths: 1, assisted: false};
+    assert_eq!(score.time_bonus(), 400);
+    assert_eq!(score.death_penalty(), 250);
+    assert_eq!(score.total(), 450);
+  }
+
+  #[test]
+  fn slow_runs_get_no_bonus_and_total_never_goes_negative() {
+    let score = Score {pickups: 0, millis: PAR_MILLIS * 2, deaths: 10, assisted: false};
+    assert_eq!(score.time_bonus(), 0);
+    assert_eq!(score.total(), 0);
+  }
+}
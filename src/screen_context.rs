@@ -4,7 +4,9 @@ type VectorPoint = mint::Point2<f32>;
 pub struct ScreenContext {
   pub translation: mint::Point2<f32>,
   pub scale: f32,
-  pub size: (f32, f32)
+  pub size: (f32, f32),
+  /// Window scale factor: physical pixels per screen pixel.
+  pub dpi: f32,
 }
 
 impl ScreenContext {
@@ -28,6 +30,17 @@ impl ScreenContext {
     }
   }
 
+  /// Moves the camera so `point` sits in the middle of the screen.
+  pub fn center_on(
+    &mut self,
+    point: VectorPoint
+  ) {
+    self.translation = VectorPoint {
+      x: point.x - self.size.0 / 2.0 / self.scale,
+      y: point.y - self.size.1 / 2.0 / self.scale,
+    };
+  }
+
   pub fn size_game_to_screen(
     &self,
     size: f32
@@ -51,7 +64,72 @@ impl Default for ScreenContext {
         y: 0.0,
       },
       scale: 1.0,
-      size: (0.0, 0.0)
+      size: (0.0, 0.0),
+      dpi: 1.0,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use proptest::prelude::*;
+
+  fn screen(x: f32, y: f32, scale: f32) -> ScreenContext {
+    ScreenContext {
+      translation: VectorPoint {x, y},
+      scale,
+      size: (800.0, 600.0),
+      dpi: 1.0,
+    }
+  }
+
+  #[test]
+  fn default_is_identity() {
+    let screen = ScreenContext::default();
+    let point = VectorPoint {x: 12.0, y: -3.0};
+    assert_eq!(screen.point_game_to_screen(point), point);
+    assert_eq!(screen.size_game_to_screen(5.0), 5.0);
+  }
+
+  #[test]
+  fn center_on_puts_point_mid_screen() {
+    let mut screen = screen(0.0, 0.0, 2.0);
+    screen.center_on(VectorPoint {x: 50.0, y: -50.0});
+    assert_eq!(screen.point_game_to_screen(VectorPoint {x: 50.0, y: -50.0}), VectorPoint {x: 400.0, y: 300.0});
+  }
+
+  #[test]
+  fn translation_and_scale() {
+    let screen = screen(100.0, 50.0, 2.0);
+    assert_eq!(screen.point_game_to_screen(VectorPoint {x: 110.0, y: 40.0}), VectorPoint {x: 20.0, y: -20.0});
+    assert_eq!(screen.size_game_to_screen(3.0), 6.0);
+    assert_eq!(screen.size_screen_to_game(6.0), 3.0);
+  }
+
+  proptest! {
+    #[test]
+    fn point_round_trip(tx in -1e4f32..1e4, ty in -1e4f32..1e4, scale in 0.01f32..100.0, x in -1e4f32..1e4, y in -1e4f32..1e4) {
+      let screen = screen(tx, ty, scale);
+      let point = VectorPoint {x, y};
+
+      let there_and_back = screen.point_game_to_screen(screen.point_screen_to_game(point));
+      let tolerance = 1e-3 * (1.0 + x.abs().max(y.abs()) + tx.abs().max(ty.abs()) * scale);
+      prop_assert!((there_and_back.x - x).abs() <= tolerance);
+      prop_assert!((there_and_back.y - y).abs() <= tolerance);
+
+      let back_and_there = screen.point_screen_to_game(screen.point_game_to_screen(point));
+      let tolerance = 1e-3 * (1.0 + x.abs().max(y.abs()) + tx.abs().max(ty.abs()));
+      prop_assert!((back_and_there.x - x).abs() <= tolerance);
+      prop_assert!((back_and_there.y - y).abs() <= tolerance);
+    }
+
+    #[test]
+    fn size_round_trip(scale in 0.01f32..100.0, size in 0.0f32..1e4) {
+      let screen = screen(0.0, 0.0, scale);
+      let back = screen.size_screen_to_game(screen.size_game_to_screen(size));
+      prop_assert!((back - size).abs() <= 1e-4 * (1.0 + size));
     }
   }
-}
\ No newline at end of file
+}
@@ -0,0 +1,283 @@
+use ggez::conf::FullscreenType;
+
+use serde::{Deserialize, Serialize};
+
+use super::input::Action;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Sample counts the renderer accepts.
+pub const MSAA_LEVELS: [u8; 5] = [1, 2, 4, 8, 16];
+
+/// How the game window takes up the screen.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayMode {
+  Windowed,
+  /// Borderless window covering the desktop; switches instantly.
+  Borderless,
+  /// Exclusive fullscreen at the monitor's video mode.
+  Fullscreen,
+}
+
+impl DisplayMode {
+  /// The mode Alt+Enter / F11 switches to.
+  pub fn next(self) -> DisplayMode {
+    match self {
+      DisplayMode::Windowed => DisplayMode::Borderless,
+      DisplayMode::Borderless => DisplayMode::Fullscreen,
+      DisplayMode::Fullscreen => DisplayMode::Windowed,
+    }
+  }
+
+  pub fn fullscreen_type(self) -> FullscreenType {
+    match self {
+      DisplayMode::Windowed => FullscreenType::Windowed,
+      DisplayMode::Borderless => FullscreenType::Desktop,
+      DisplayMode::Fullscreen => FullscreenType::True,
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      DisplayMode::Windowed => "Windowed",
+      DisplayMode::Borderless => "Borderless",
+      DisplayMode::Fullscreen => "Fullscreen",
+    }
+  }
+}
+
+/// Player-facing options, read from the config file, overridden by command
+/// line flags and saved again on exit.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+  /// Draws where the ship would go if released now. Runs that use it don't
+  /// count for ranked times.
+  pub aim_assist: bool,
+  /// Whether the F3 debug overlay, with the framerate, starts shown.
+  pub show_debug: bool,
+  pub display_mode: DisplayMode,
+  /// Size of the window when windowed, kept while fullscreen.
+  pub window_width: f32,
+  pub window_height: f32,
+  /// MSAA sample count, one of `MSAA_LEVELS`. Applies on restart.
+  pub msaa: u8,
+  /// Applies on restart.
+  pub vsync: bool,
+  /// Level to start on instead of the saved one.
+  pub start_level: Option<usize>,
+  /// Level file to load instead of the bundled one.
+  pub levels_path: Option<PathBuf>,
+  /// Keys the player rebound from the controls menu, by key name. Each
+  /// replaces the action's default keys.
+  pub keys: BTreeMap<Action, String>,
+}
+
+impl Default for Settings {
+  fn default() -> Settings {
+    Settings {
+      aim_assist: false,
+      show_debug: false,
+      display_mode: DisplayMode::Windowed,
+      window_width: 800.0,
+      window_height: 600.0,
+      msaa: 16,
+      vsync: true,
+      start_level: None,
+      levels_path: None,
+      keys: BTreeMap::new(),
+    }
+  }
+}
+
+impl Settings {
+  /// Falls back to defaults if the file is missing or unreadable.
+  pub fn load(path: &Path) -> Settings {
+    std::fs::read_to_string(path)
+      .ok()
+      .and_then(|file_string| serde_json::from_str(&file_string).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn save(
+    &self,
+    path: &Path,
+  ) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+      std::fs::create_dir_all(dir)?;
+    }
+
+    let json_string = serde_json::to_string_pretty(self)
+      .map_err(std::io::Error::other)?;
+
+    std::fs::write(path, json_string)
+  }
+
+  /// These settings with the command line flags applied on top.
+  pub fn with_args(
+    &self,
+    args: &Args,
+  ) -> Settings {
+    let mut settings = self.clone();
+    if args.level.is_some() {
+      settings.start_level = args.level;
+    }
+    if args.windowed {
+      settings.display_mode = DisplayMode::Windowed;
+    }
+    if let Some(msaa) = args.msaa {
+      settings.msaa = msaa;
+    }
+    if args.no_vsync {
+      settings.vsync = false;
+    }
+    if args.levels.is_some() {
+      settings.levels_path = args.levels.clone();
+    }
+    settings
+  }
+
+  /// Steps `msaa` to the next supported sample count, wrapping around.
+  pub fn cycle_msaa(&mut self) {
+    let current = MSAA_LEVELS.iter().position(|&level| level == self.msaa).unwrap_or(0);
+    self.msaa = MSAA_LEVELS[(current + 1) % MSAA_LEVELS.len()];
+  }
+}
+
+/// Command line flags. They win over the config file for this session only.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Args {
+  pub level: Option<usize>,
+  pub windowed: bool,
+  pub msaa: Option<u8>,
+  pub no_vsync: bool,
+  pub levels: Option<PathBuf>,
+}
+
+pub const USAGE: &str = "\
+Usage: skyship_rust [options]
+  --level N       start on level N (0-based)
+  --windowed      run in a window even if fullscreen is configured
+  --msaa [N]      MSAA sample count: 1, 2, 4, 8 or 16 (16 if omitted)
+  --no-vsync      disable vsync
+  --levels PATH   load levels from PATH instead of the bundled file";
+
+impl Args {
+  /// Parses the flags after the program name.
+  pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter().peekable();
+
+    while let Some(arg) = args.next() {
+      match arg.as_str() {
+        "--level" => {
+          let value = args.next().ok_or("--level needs a level number")?;
+          parsed.level = Some(value.parse().map_err(|_| format!("invalid level: {}", value))?);
+        }
+        "--windowed" => parsed.windowed = true,
+        "--msaa" => {
+          let samples = match args.peek().and_then(|value| value.parse::<u8>().ok()) {
+            Some(samples) => {
+              args.next();
+              samples
+            }
+            None => 16,
+          };
+          if !MSAA_LEVELS.contains(&samples) {
+            return Err(format!("unsupported MSAA sample count: {}", samples));
+          }
+          parsed.msaa = Some(samples);
+        }
+        "--no-vsync" => parsed.no_vsync = true,
+        "--levels" => {
+          let value = args.next().ok_or("--levels needs a path")?;
+          parsed.levels = Some(PathBuf::from(value));
+        }
+        other => return Err(format!("unknown argument: {}", other)),
+      }
+    }
+
+    Ok(parsed)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(args: &[&str]) -> Result<Args, String> {
+    Args::parse(args.iter().map(|arg| arg.to_string()))
+  }
+
+  #[test]
+  fn parses_every_flag() {
+    let args = parse(&["--level", "3", "--windowed", "--msaa", "4", "--no-vsync", "--levels", "custom.levelData"]).unwrap();
+    assert_eq!(args, Args {
+      level: Some(3),
+      windowed: true,
+      msaa: Some(4),
+      no_vsync: true,
+      levels: Some(PathBuf::from("custom.levelData")),
+    });
+  }
+
+  #[test]
+  fn msaa_value_is_optional() {
+    assert_eq!(parse(&["--msaa", "--windowed"]).unwrap().msaa, Some(16));
+    assert!(parse(&["--msaa", "3"]).is_err());
+  }
+
+  #[test]
+  fn rejects_bad_input() {
+    assert!(parse(&["--level"]).is_err());
+    assert!(parse(&["--level", "two"]).is_err());
+    assert!(parse(&["--fast"]).is_err());
+  }
+
+  #[test]
+  fn args_override_file_settings() {
+    let file = Settings {display_mode: DisplayMode::Borderless, start_level: Some(1), ..Settings::default()};
+    let merged = file.with_args(&parse(&["--windowed", "--level", "2", "--no-vsync"]).unwrap());
+
+    assert_eq!(merged.display_mode, DisplayMode::Windowed);
+    assert!(!merged.vsync);
+    assert_eq!(merged.start_level, Some(2));
+    assert_eq!(merged.msaa, file.msaa);
+  }
+
+  #[test]
+  fn rebound_keys_round_trip() {
+    let mut settings = Settings::default();
+    settings.keys.insert(Action::Grab, "G".to_string());
+
+    let json_string = serde_json::to_string(&settings).unwrap();
+    assert!(json_string.contains(r#""keys":{"grab":"G"}"#));
+    assert_eq!(serde_json::from_str::<Settings>(&json_string).unwrap(), settings);
+  }
+
+  #[test]
+  fn missing_fields_fall_back_to_defaults() {
+    let settings: Settings = serde_json::from_str(r#"{"aim_assist": true}"#).unwrap();
+    assert!(settings.aim_assist);
+    assert_eq!(settings.window_width, 800.0);
+  }
+
+  #[test]
+  fn display_modes_cycle_and_round_trip() {
+    let mut mode = DisplayMode::Windowed;
+    let mut seen = vec![];
+    for _ in 0..3 {
+      seen.push(mode);
+      mode = mode.next();
+    }
+    assert_eq!(mode, DisplayMode::Windowed);
+    assert_eq!(seen, [DisplayMode::Windowed, DisplayMode::Borderless, DisplayMode::Fullscreen]);
+
+    let settings: Settings = serde_json::from_str(r#"{"display_mode": "borderless"}"#).unwrap();
+    assert_eq!(settings.display_mode, DisplayMode::Borderless);
+    let json_string = serde_json::to_string(&settings).unwrap();
+    assert_eq!(serde_json::from_str::<Settings>(&json_string).unwrap(), settings);
+  }
+}
@@ -9,63 +9,587 @@ pub mod rstrt;
 pub mod winng;
 pub mod conti;
 
+pub mod pause;
+pub mod setng;
+pub mod ctrls;
+pub mod cnfrm;
+
 
 use super::screen_context::ScreenContext;
 use super::game_objects::level::Level;
+use super::game_objects::ship::Ship;
+use super::input::InputState;
+use super::settings::Settings;
 use ggez::{GameResult, Context};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+
+pub enum Transition {
+  None,
+  Switch(Box<dyn GameState>),
+  Push(Box<dyn GameState>),
+  Pop,
+  /// Drops the whole stack, e.g. leaving a paused run for the menu.
+  Clear(Box<dyn GameState>),
+  Quit,
+}
+
+pub trait GameState {
+  fn update(
+    &mut self,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    settings: &mut Settings,
+    input: &InputState,
+    millis_since_state_start: u64,
+  ) -> Transition;
+
+  fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    level: &Level,
+    millis_since_state_start: u64,
+  ) -> GameResult<()>;
+
+  fn on_enter(
+    &mut self,
+    _screen: &mut ScreenContext,
+    _level: &mut Level,
+  ) {}
+
+  fn on_exit(
+    &mut self,
+    _screen: &mut ScreenContext,
+    _level: &mut Level,
+  ) {}
 
-pub enum State {
-  Menus,
+  fn key_down_event(
+    &mut self,
+    _key: KeyCode,
+    _mods: KeyMods,
+  ) -> Transition {
+    Transition::None
+  }
 
-  Start,
-  Flyng,
+  fn key_up_event(
+    &mut self,
+    _key: KeyCode,
+    _mods: KeyMods,
+  ) -> Transition {
+    Transition::None
+  }
 
-  Dying,
-  Rstrt,
+  fn mouse_button_down_event(
+    &mut self,
+    _button: MouseButton,
+    _point: mint::Point2<f32>,
+  ) -> Transition {
+    Transition::None
+  }
 
-  Winng,
-  Conti,
+  fn mouse_button_up_event(
+    &mut self,
+    _button: MouseButton,
+    _point: mint::Point2<f32>,
+  ) -> Transition {
+    Transition::None
+  }
+
+  /// Whether quitting from this state would throw away a level in progress.
+  fn in_run(&self) -> bool {
+    false
+  }
+
+  /// Whether this state is the finished-level screen, so the win can be
+  /// saved to progress.
+  fn level_complete(&self) -> bool {
+    false
+  }
+
+  /// Whether this state is already asking the player to confirm quitting.
+  fn asks_to_quit(&self) -> bool {
+    false
+  }
+
+  /// Whether this state is a menu changing `Settings`, which are saved
+  /// once it closes.
+  fn edits_settings(&self) -> bool {
+    false
+  }
+
+  /// Overlays (e.g. pause) are drawn on top of the state beneath them
+  /// instead of replacing it.
+  fn is_overlay(&self) -> bool {
+    false
+  }
+
+  /// Short name shown in the debug overlay.
+  fn name(&self) -> &'static str {
+    let path = std::any::type_name::<Self>();
+    path.rsplit("::").next().unwrap_or(path)
+  }
+
+  /// The ship this state is simulating, if any.
+  fn ship(&self) -> Option<&Ship> {
+    None
+  }
+
+  /// Extra state-specific lines for the debug overlay.
+  fn debug_lines(&self) -> Vec<String> {
+    Vec::new()
+  }
 }
 
-impl State {
-  pub fn update(
-    &self,
+/// Most updates one tick runs while states hand over to each other. Two
+/// states that switch back and forth on held input would otherwise spin
+/// forever; past this the chain carries on next tick.
+const MAX_CHAINED_UPDATES: usize = 8;
+
+struct StackEntry {
+  state: Box<dyn GameState>,
+  start: u64,
+  covered_at: Option<u64>,
+}
+
+impl StackEntry {
+  /// States covered by another state don't age, so a paused countdown picks
+  /// up where it left off.
+  fn millis_since_start(&self, now: u64) -> u64 {
+    self.covered_at.unwrap_or(now) - self.start
+  }
+}
+
+pub struct StateMachine {
+  stack: Vec<StackEntry>,
+  quit_requested: bool,
+}
+
+impl StateMachine {
+  pub fn new(
+    initial: Box<dyn GameState>,
     screen: &mut ScreenContext,
     level: &mut Level,
-    millis_since_state_start: u64,
-  ) -> Option<State> {
-    match self {
-      State::Menus => menus::update(screen, level, millis_since_state_start),
+    now: u64,
+  ) -> StateMachine {
+    let mut machine = StateMachine {
+      stack: Vec::new(),
+      quit_requested: false,
+    };
+    machine.push(initial, screen, level, now);
+    machine
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.stack.is_empty()
+  }
+
+  pub fn quit_requested(&self) -> bool {
+    self.quit_requested
+  }
+
+  /// Names of the stacked states, bottom first, with how long the top one
+  /// has been running.
+  pub fn describe(
+    &self,
+    now: u64,
+  ) -> (Vec<&'static str>, u64) {
+    let names = self.stack.iter().map(|entry| entry.state.name()).collect();
+    let millis = self.stack.last().map_or(0, |entry| entry.millis_since_start(now));
+    (names, millis)
+  }
+
+  /// The topmost state that has a ship, so it still shows under a pause.
+  pub fn ship(&self) -> Option<&Ship> {
+    self.stack.iter().rev().find_map(|entry| entry.state.ship())
+  }
 
-      State::Start => start::update(screen, level, millis_since_state_start),
-      State::Flyng => flyng::update(screen, level, millis_since_state_start),
+  /// Debug lines from the topmost state that has any.
+  pub fn debug_lines(&self) -> Vec<String> {
+    self.stack.iter().rev().map(|entry| entry.state.debug_lines()).find(|lines| !lines.is_empty()).unwrap_or_default()
+  }
 
-      State::Dying => dying::update(screen, level, millis_since_state_start),
-      State::Rstrt => rstrt::update(screen, level, millis_since_state_start),
+  pub fn in_run(&self) -> bool {
+    self.stack.last().is_some_and(|entry| entry.state.in_run())
+  }
 
-      State::Winng => winng::update(screen, level, millis_since_state_start),
-      State::Conti => conti::update(screen, level, millis_since_state_start),
+  pub fn level_complete(&self) -> bool {
+    self.stack.last().is_some_and(|entry| entry.state.level_complete())
+  }
+
+  pub fn asks_to_quit(&self) -> bool {
+    self.stack.last().is_some_and(|entry| entry.state.asks_to_quit())
+  }
+
+  pub fn edits_settings(&self) -> bool {
+    self.stack.last().is_some_and(|entry| entry.state.edits_settings())
+  }
+
+  pub fn update(
+    &mut self,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    settings: &mut Settings,
+    input: &InputState,
+    now: u64,
+  ) {
+    // A state that transitions gets its successor updated in the same tick,
+    // the same way the old enum dispatch re-ran `update` after a switch. The
+    // successor only sees held actions so one press can't fire twice.
+    let held_only = input.without_edges();
+    let mut input = input;
+    for _ in 0..MAX_CHAINED_UPDATES {
+      let transition = match self.stack.last_mut() {
+        Some(entry) => entry.state.update(screen, level, settings, input, entry.millis_since_start(now)),
+        None => return,
+      };
+      if !self.apply(transition, screen, level, now) {
+        return;
+      }
+      input = &held_only;
     }
-  } 
-  
+  }
+
   pub fn draw(
     &self,
     ctx: &mut Context,
     screen: &ScreenContext,
     level: &Level,
-    millis_since_state_start: u64,
+    now: u64,
   ) -> GameResult<()> {
-    match self {
-      State::Menus => menus::draw(ctx, screen, level, millis_since_state_start),
+    let base = self.stack.iter().rposition(|entry| !entry.state.is_overlay()).unwrap_or(0);
+
+    for entry in self.stack.iter().skip(base) {
+      entry.state.draw(ctx, screen, level, entry.millis_since_start(now))?;
+    }
+
+    Ok(())
+  }
 
-      State::Start => start::draw(ctx, screen, level, millis_since_state_start),
-      State::Flyng => flyng::draw(ctx, screen, level, millis_since_state_start),
+  pub fn key_down_event(
+    &mut self,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    key: KeyCode,
+    mods: KeyMods,
+    now: u64,
+  ) {
+    if let Some(entry) = self.stack.last_mut() {
+      let transition = entry.state.key_down_event(key, mods);
+      self.apply(transition, screen, level, now);
+    }
+  }
 
-      State::Dying => dying::draw(ctx, screen, level, millis_since_state_start),
-      State::Rstrt => rstrt::draw(ctx, screen, level, millis_since_state_start),
+  pub fn key_up_event(
+    &mut self,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    key: KeyCode,
+    mods: KeyMods,
+    now: u64,
+  ) {
+    if let Some(entry) = self.stack.last_mut() {
+      let transition = entry.state.key_up_event(key, mods);
+      self.apply(transition, screen, level, now);
+    }
+  }
 
-      State::Winng => winng::draw(ctx, screen, level, millis_since_state_start),
-      State::Conti => conti::draw(ctx, screen, level, millis_since_state_start),
+  pub fn mouse_button_down_event(
+    &mut self,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    button: MouseButton,
+    point: mint::Point2<f32>,
+    now: u64,
+  ) {
+    if let Some(entry) = self.stack.last_mut() {
+      let transition = entry.state.mouse_button_down_event(button, point);
+      self.apply(transition, screen, level, now);
     }
-  } 
-}
\ No newline at end of file
+  }
+
+  pub fn mouse_button_up_event(
+    &mut self,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    button: MouseButton,
+    point: mint::Point2<f32>,
+    now: u64,
+  ) {
+    if let Some(entry) = self.stack.last_mut() {
+      let transition = entry.state.mouse_button_up_event(button, point);
+      self.apply(transition, screen, level, now);
+    }
+  }
+
+  /// Returns whether the active state changed.
+  fn apply(
+    &mut self,
+    transition: Transition,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    now: u64,
+  ) -> bool {
+    match transition {
+      Transition::None => false,
+      Transition::Switch(state) => {
+        self.pop(screen, level, now);
+        self.push(state, screen, level, now);
+        true
+      }
+      Transition::Push(state) => {
+        self.push(state, screen, level, now);
+        true
+      }
+      Transition::Pop => {
+        self.pop(screen, level, now);
+        true
+      }
+      Transition::Clear(state) => {
+        while !self.stack.is_empty() {
+          self.pop(screen, level, now);
+        }
+        self.push(state, screen, level, now);
+        true
+      }
+      Transition::Quit => {
+        self.quit_requested = true;
+        false
+      }
+    }
+  }
+
+  pub fn push(
+    &mut self,
+    mut state: Box<dyn GameState>,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    now: u64,
+  ) {
+    if let Some(entry) = self.stack.last_mut() {
+      entry.covered_at = Some(now);
+    }
+
+    state.on_enter(screen, level);
+    self.stack.push(StackEntry { state, start: now, covered_at: None });
+  }
+
+  fn pop(
+    &mut self,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    now: u64,
+  ) {
+    if let Some(mut entry) = self.stack.pop() {
+      entry.state.on_exit(screen, level);
+    }
+
+    if let Some(entry) = self.stack.last_mut() {
+      if let Some(covered_at) = entry.covered_at.take() {
+        entry.start += now - covered_at;
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::input::{Action, Binding};
+
+  use std::cell::RefCell;
+  use std::collections::VecDeque;
+  use std::rc::Rc;
+
+  type Log = Rc<RefCell<Vec<String>>>;
+
+  /// Logs what the machine does to it and answers each update with the next
+  /// transition from `next`.
+  struct Stub {
+    name: &'static str,
+    log: Log,
+    next: Box<dyn FnMut(&InputState) -> Transition>,
+  }
+
+  impl GameState for Stub {
+    fn update(
+      &mut self,
+      _screen: &mut ScreenContext,
+      _level: &mut Level,
+      _settings: &mut Settings,
+      input: &InputState,
+      millis_since_state_start: u64,
+    ) -> Transition {
+      self.log.borrow_mut().push(format!("{} update {}", self.name, millis_since_state_start));
+      (self.next)(input)
+    }
+
+    fn draw(
+      &self,
+      _ctx: &mut Context,
+      _screen: &ScreenContext,
+      _level: &Level,
+      _millis_since_state_start: u64,
+    ) -> GameResult<()> {
+      Ok(())
+    }
+
+    fn on_enter(
+      &mut self,
+      _screen: &mut ScreenContext,
+      _level: &mut Level,
+    ) {
+      self.log.borrow_mut().push(format!("{} enter", self.name));
+    }
+
+    fn on_exit(
+      &mut self,
+      _screen: &mut ScreenContext,
+      _level: &mut Level,
+    ) {
+      self.log.borrow_mut().push(format!("{} exit", self.name));
+    }
+
+    fn name(&self) -> &'static str {
+      self.name
+    }
+  }
+
+  /// A stub that plays `script` out one update at a time, then stays put.
+  fn scripted(name: &'static str, log: &Log, script: Vec<Transition>) -> Box<dyn GameState> {
+    let mut script: VecDeque<Transition> = script.into();
+    Box::new(Stub {name, log: log.clone(), next: Box::new(move |_| script.pop_front().unwrap_or(Transition::None))})
+  }
+
+  /// A stub that always switches to a fresh copy of itself.
+  fn pinger(log: &Log) -> Box<dyn GameState> {
+    let next_log = log.clone();
+    Box::new(Stub {name: "ping", log: log.clone(), next: Box::new(move |_| Transition::Switch(pinger(&next_log)))})
+  }
+
+  struct World {
+    machine: StateMachine,
+    screen: ScreenContext,
+    level: Level,
+    settings: Settings,
+    log: Log,
+  }
+
+  impl World {
+    fn new(initial: impl FnOnce(&Log) -> Box<dyn GameState>) -> World {
+      let log = Log::default();
+      let mut screen = ScreenContext::default();
+      let mut level = Level::default();
+      let machine = StateMachine::new(initial(&log), &mut screen, &mut level, 0);
+      World {machine, screen, level, settings: Settings::default(), log}
+    }
+
+    fn update(&mut self, input: &InputState, now: u64) {
+      self.machine.update(&mut self.screen, &mut self.level, &mut self.settings, input, now);
+    }
+
+    /// Everything logged since the last call.
+    fn take_log(&self) -> Vec<String> {
+      self.log.borrow_mut().drain(..).collect()
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+      self.machine.describe(u64::MAX).0
+    }
+  }
+
+  #[test]
+  fn push_covers_and_pop_uncovers() {
+    let mut world = World::new(|log| {
+      let pushed = scripted("b", log, vec![Transition::None, Transition::None, Transition::Pop]);
+      scripted("a", log, vec![Transition::Push(pushed)])
+    });
+    world.take_log();
+
+    world.update(&InputState::default(), 100);
+    assert_eq!(world.names(), ["a", "b"]);
+    assert_eq!(world.take_log(), ["a update 100", "b enter", "b update 0"]);
+
+    // `a` was covered from 100 to 250, so it picks up at 100, not 250.
+    world.update(&InputState::default(), 150);
+    world.update(&InputState::default(), 250);
+    assert_eq!(world.names(), ["a"]);
+    assert_eq!(world.take_log(), ["b update 50", "b update 150", "b exit", "a update 100"]);
+
+    world.update(&InputState::default(), 300);
+    assert_eq!(world.take_log(), ["a update 150"]);
+  }
+
+  #[test]
+  fn switch_replaces_only_the_top() {
+    let mut world = World::new(|log| {
+      let switched = scripted("b", log, vec![Transition::None, Transition::Switch(scripted("c", log, vec![]))]);
+      scripted("a", log, vec![Transition::Push(switched)])
+    });
+    world.update(&InputState::default(), 0);
+    world.take_log();
+
+    world.update(&InputState::default(), 40);
+    assert_eq!(world.names(), ["a", "c"]);
+    assert_eq!(world.take_log(), ["b update 40", "b exit", "c enter", "c update 0"]);
+  }
+
+  #[test]
+  fn clear_drops_the_whole_stack() {
+    let mut world = World::new(|log| {
+      let cleared = scripted("b", log, vec![Transition::None, Transition::Clear(scripted("c", log, vec![]))]);
+      scripted("a", log, vec![Transition::Push(cleared)])
+    });
+    world.update(&InputState::default(), 0);
+    world.take_log();
+
+    world.update(&InputState::default(), 10);
+    assert_eq!(world.names(), ["c"]);
+    assert_eq!(world.take_log(), ["b update 10", "b exit", "a exit", "c enter", "c update 0"]);
+  }
+
+  #[test]
+  fn quit_flags_without_changing_state() {
+    let mut world = World::new(|log| scripted("a", log, vec![Transition::Quit]));
+    world.take_log();
+
+    assert!(!world.machine.quit_requested());
+    world.update(&InputState::default(), 10);
+    assert!(world.machine.quit_requested());
+    assert_eq!(world.names(), ["a"]);
+    assert_eq!(world.take_log(), ["a update 10"]);
+  }
+
+  #[test]
+  fn successor_only_sees_held_actions() {
+    let mut world = World::new(|log| {
+      let log = log.clone();
+      Box::new(Stub {name: "a", log: log.clone(), next: Box::new(move |input| {
+        log.borrow_mut().push(format!("a pressed {}", input.was_pressed(Action::Confirm)));
+        let log = log.clone();
+        Transition::Switch(Box::new(Stub {name: "b", log: log.clone(), next: Box::new(move |input| {
+          log.borrow_mut().push(format!("b pressed {} held {}", input.was_pressed(Action::Confirm), input.is_held(Action::Confirm)));
+          Transition::None
+        })}))
+      })})
+    });
+    world.take_log();
+
+    let mut input = InputState::default();
+    input.press(Binding::Key(KeyCode::Return), Action::Confirm);
+    world.update(&input, 0);
+    let log = world.take_log();
+    assert!(log.contains(&"a pressed true".to_string()));
+    assert!(log.contains(&"b pressed false held true".to_string()));
+  }
+
+  #[test]
+  fn endless_switching_is_cut_off_each_tick() {
+    let mut world = World::new(pinger);
+    world.take_log();
+
+    world.update(&InputState::default(), 0);
+    let updates = world.take_log().iter().filter(|line| line.contains("update")).count();
+    assert_eq!(updates, MAX_CHAINED_UPDATES);
+    assert_eq!(world.names(), ["ping"]);
+  }
+}
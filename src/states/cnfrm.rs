@@ -0,0 +1,68 @@
+use super::super::screen_context::ScreenContext;
+use super::super::game_objects::level::Level;
+use super::super::settings::Settings;
+use super::super::input::{Action, InputState};
+use super::super::drawing_helpers::menu::{draw_dim, draw_options, move_cursor};
+use super::{GameState, Transition};
+
+use ggez::{GameResult, Context};
+
+const OPTIONS: [&str; 2] = ["Keep Playing", "Quit"];
+
+/// Asks before quitting in the middle of a run.
+#[derive(Default)]
+pub struct Cnfrm {
+  pub cursor: usize,
+}
+
+impl GameState for Cnfrm {
+  fn update(
+    &mut self,
+    _screen: &mut ScreenContext,
+    _level: &mut Level,
+    _settings: &mut Settings,
+    input: &InputState,
+    _millis_since_state_start: u64
+  ) -> Transition {
+    if input.was_pressed(Action::Pause) || input.was_pressed(Action::Back) {
+      return Transition::Pop;
+    }
+
+    self.cursor = move_cursor(
+      self.cursor,
+      OPTIONS.len(),
+      input.was_pressed(Action::Up),
+      input.was_pressed(Action::Down)
+    );
+
+    if input.was_pressed(Action::Confirm) {
+      match self.cursor {
+        0 => Transition::Pop,
+        _ => Transition::Quit,
+      }
+    } else {
+      Transition::None
+    }
+  }
+
+  fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    _level: &Level,
+    _millis_since_state_start: u64
+  ) -> GameResult<()> {
+    draw_dim(ctx, screen, 0.6)?;
+    draw_options(ctx, screen, &OPTIONS, self.cursor)?;
+
+    Ok(())
+  }
+
+  fn is_overlay(&self) -> bool {
+    true
+  }
+
+  fn asks_to_quit(&self) -> bool {
+    true
+  }
+}
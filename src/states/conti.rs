@@ -1,22 +1,32 @@
 use super::super::screen_context::ScreenContext;
 use super::super::game_objects::level::Level;
-use super::State;
+use super::super::settings::Settings;
+use super::super::input::InputState;
+use super::{GameState, Transition};
 
 use ggez::{GameResult, Context};
 
-pub fn update(
-  screen: &mut ScreenContext,
-  level: &mut Level,
-  millis_since_state_start: u64
-) -> Option<State> {
-  None
-}
+pub struct Conti;
 
-pub fn draw(
-  ctx: &mut Context,
-  screen: &ScreenContext,
-  level: &Level,
-  millis_since_state_start: u64
-) -> GameResult<()> {
-  Ok(())
+impl GameState for Conti {
+  fn update(
+    &mut self,
+    _screen: &mut ScreenContext,
+    _level: &mut Level,
+    _settings: &mut Settings,
+    _input: &InputState,
+    _millis_since_state_start: u64
+  ) -> Transition {
+    Transition::None
+  }
+
+  fn draw(
+    &self,
+    _ctx: &mut Context,
+    _screen: &ScreenContext,
+    _level: &Level,
+    _millis_since_state_start: u64
+  ) -> GameResult<()> {
+    Ok(())
+  }
 }
\ No newline at end of file
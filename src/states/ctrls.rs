@@ -0,0 +1,129 @@
+use super::super::screen_context::ScreenContext;
+use super::super::game_objects::level::Level;
+use super::super::settings::Settings;
+use super::super::input::{self, Action, Binding, InputBindings, InputState};
+use super::super::drawing_helpers::menu::{draw_dim, draw_options, move_cursor};
+use super::{GameState, Transition};
+
+use ggez::{GameResult, Context};
+use ggez::event::{KeyCode, KeyMods};
+
+/// Actions the player can move to other keys. Menu keys stay put so a bad
+/// binding can always be undone.
+const REBINDABLE: [Action; 3] = [Action::Grab, Action::Pause, Action::Restart];
+
+/// Controls menu, opened over the settings menu. Picking an action waits
+/// for the next key and binds the action to it in `Settings::keys`.
+#[derive(Default)]
+pub struct Ctrls {
+  pub cursor: usize,
+  /// Action waiting for its new key.
+  waiting: Option<Action>,
+  /// Key pressed since the last update.
+  key: Option<KeyCode>,
+  labels: Vec<String>,
+}
+
+impl Ctrls {
+  fn labels(&self, settings: &Settings) -> Vec<String> {
+    let bindings = InputBindings::with_keys(&settings.keys);
+    let mut labels: Vec<String> = REBINDABLE.iter().map(|&action| {
+      if self.waiting == Some(action) {
+        return format!("{:?}: press a key (Escape cancels)", action);
+      }
+      let mut keys: Vec<String> = bindings.bindings_for(action).into_iter().filter_map(|binding| match binding {
+        Binding::Key(key) => Some(format!("{:?}", key)),
+        _ => None,
+      }).collect();
+      keys.sort();
+      format!("{:?}: {}", action, if keys.is_empty() {"None".to_string()} else {keys.join(" / ")})
+    }).collect();
+    labels.push("Reset to Defaults".to_string());
+    labels.push("Back".to_string());
+    labels
+  }
+}
+
+impl GameState for Ctrls {
+  fn update(
+    &mut self,
+    _screen: &mut ScreenContext,
+    _level: &mut Level,
+    settings: &mut Settings,
+    input: &InputState,
+    _millis_since_state_start: u64
+  ) -> Transition {
+    // Only keys pressed while waiting count, not the one that started it.
+    let key = self.key.take();
+
+    if let Some(action) = self.waiting {
+      match key {
+        Some(KeyCode::Escape) => self.waiting = None,
+        Some(key) => if let Some(name) = input::key_name(key) {
+          settings.keys.insert(action, name);
+          self.waiting = None;
+        },
+        None => (),
+      }
+      self.labels = self.labels(settings);
+      return Transition::None;
+    }
+
+    if input.was_pressed(Action::Pause) || input.was_pressed(Action::Back) {
+      return Transition::Pop;
+    }
+
+    let option_count = REBINDABLE.len() + 2;
+    self.cursor = move_cursor(
+      self.cursor,
+      option_count,
+      input.was_pressed(Action::Up),
+      input.was_pressed(Action::Down)
+    );
+
+    if input.was_pressed(Action::Confirm) {
+      match REBINDABLE.get(self.cursor) {
+        Some(&action) => self.waiting = Some(action),
+        None if self.cursor == REBINDABLE.len() => settings.keys.clear(),
+        None => return Transition::Pop,
+      }
+    }
+    self.labels = self.labels(settings);
+    Transition::None
+  }
+
+  fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    _level: &Level,
+    _millis_since_state_start: u64
+  ) -> GameResult<()> {
+    draw_dim(ctx, screen, 0.85)?;
+    let options: Vec<&str> = self.labels.iter().map(|label| label.as_str()).collect();
+    draw_options(ctx, screen, &options, self.cursor)?;
+
+    Ok(())
+  }
+
+  fn key_down_event(
+    &mut self,
+    key: KeyCode,
+    _mods: KeyMods,
+  ) -> Transition {
+    self.key = Some(key);
+    Transition::None
+  }
+
+  fn is_overlay(&self) -> bool {
+    true
+  }
+
+  fn in_run(&self) -> bool {
+    true
+  }
+
+  fn edits_settings(&self) -> bool {
+    true
+  }
+}
@@ -1,22 +1,85 @@
 use super::super::screen_context::ScreenContext;
 use super::super::game_objects::level::Level;
-use super::State;
+use super::super::game_objects::point::Point;
+use super::super::settings::Settings;
+use super::super::input::InputState;
+use super::{GameState, Transition};
+use super::rstrt::Rstrt;
+use super::flyng::Respawn;
 
 use ggez::{GameResult, Context};
+use ggez::graphics;
 
-pub fn update(
-  screen: &mut ScreenContext,
-  level: &mut Level,
-  millis_since_state_start: u64
-) -> Option<State> {
-  None
+const DEATH_MILLIS: u64 = 1000;
+
+pub struct Dying {
+  pub death_pos: Point,
+  pub deaths: u32,
+  pub respawn: Option<Respawn>,
+  last_millis: u64,
+}
+
+impl Dying {
+  /// `deaths` counts earlier attempts; this one is added on restart.
+  pub fn new(
+    death_pos: Point,
+    deaths: u32,
+    respawn: Option<Respawn>,
+  ) -> Dying {
+    Dying {
+      death_pos,
+      deaths,
+      respawn,
+      last_millis: 0,
+    }
+  }
 }
 
-pub fn draw(
-  ctx: &mut Context,
-  screen: &ScreenContext,
-  level: &Level,
-  millis_since_state_start: u64
-) -> GameResult<()> {
-  Ok(())
-}
\ No newline at end of file
+impl GameState for Dying {
+  fn update(
+    &mut self,
+    _screen: &mut ScreenContext,
+    level: &mut Level,
+    _settings: &mut Settings,
+    _input: &InputState,
+    millis_since_state_start: u64
+  ) -> Transition {
+    level.particles.update(millis_since_state_start.saturating_sub(self.last_millis) as f32);
+    self.last_millis = millis_since_state_start;
+
+    if millis_since_state_start >= DEATH_MILLIS {
+      Transition::Switch(Box::new(Rstrt {deaths: self.deaths + 1, respawn: self.respawn.take()}))
+    } else {
+      Transition::None
+    }
+  }
+
+  fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    level: &Level,
+    millis_since_state_start: u64
+  ) -> GameResult<()> {
+    level.draw_level(ctx, screen)?;
+
+    let progress = millis_since_state_start as f32 / DEATH_MILLIS as f32;
+
+    let burst = graphics::Mesh::new_circle(
+      ctx,
+      graphics::DrawMode::stroke(3.0),
+      screen.point_game_to_screen(self.death_pos.into()),
+      screen.size_game_to_screen(10.0 + progress * 60.0),
+      0.5,
+      [1.0, 0.4, 0.1, 1.0 - progress.min(1.0)].into()
+    )?;
+    graphics::draw(ctx, &burst, graphics::DrawParam::default())?;
+    level.particles.draw(ctx, screen)?;
+
+    Ok(())
+  }
+
+  fn in_run(&self) -> bool {
+    true
+  }
+}
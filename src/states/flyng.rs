@@ -1,24 +1,285 @@
 use super::super::screen_context::ScreenContext;
 use super::super::game_objects::level::Level;
-use super::State;
+use super::super::game_objects::ship::Ship;
+use super::super::game_objects::trail::Trail;
+use super::super::settings::Settings;
+use super::super::input::{Action, InputState};
+use super::super::clock::{self, tick_millis};
+use super::{GameState, Transition};
+use super::pause::Pause;
+use super::dying::Dying;
+use super::rstrt::Rstrt;
+use super::winng::Winng;
+use super::super::geometry::{Segment, Vec2};
+use super::super::score::Score;
+use super::super::particles::Emitter;
+use super::super::drawing_helpers::ui::{Anchor, Layout};
 
 use ggez::{GameResult, Context};
+use ggez::graphics::{self, TextFragment};
 
-pub fn update(
-  screen: &mut ScreenContext,
-  level: &mut Level,
-  millis_since_state_start: u64
-) -> Option<State> {
-  None
+/// How long a split time stays on screen after crossing a checkpoint.
+const SPLIT_SHOW_MILLIS: u64 = 2000;
+/// How far ahead the aim assist simulates a release.
+const PREVIEW_TICKS: usize = 120;
+
+/// Everything needed to pick a run back up from the last checkpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Respawn {
+  pub ship: Ship,
+  /// Ticks simulated when the snapshot was taken.
+  pub ticks: u64,
+  pub pickups: u32,
+  pub collected: Vec<usize>,
+  pub splits: Vec<u64>,
+  pub assisted: bool,
+}
+
+pub struct Flyng {
+  pub ship: Ship,
+  ticks: u64,
+  simulated_millis: u64,
+  /// Ticks simulated when this state was entered; non-zero after a respawn.
+  resumed_from: u64,
+  grab_queued: bool,
+  pickups: u32,
+  deaths: u32,
+  /// Run time at each checkpoint crossed so far.
+  splits: Vec<u64>,
+  respawn: Option<Respawn>,
+  assisted: bool,
+  trail: Trail,
+  /// Predicted path if the ship let go now; empty unless aim assist is on
+  /// and the ship is orbiting.
+  preview: Vec<Vec2>,
+}
+
+impl Flyng {
+  pub fn new(
+    ship: Ship,
+    deaths: u32,
+  ) -> Flyng {
+    Flyng {
+      ship,
+      ticks: 0,
+      simulated_millis: 0,
+      resumed_from: 0,
+      grab_queued: false,
+      pickups: 0,
+      deaths,
+      splits: Vec::new(),
+      respawn: None,
+      assisted: false,
+      trail: Trail::default(),
+      preview: Vec::new(),
+    }
+  }
+
+  /// Continues a run from `respawn`. The level must already be restored to
+  /// match it.
+  pub fn resume(
+    respawn: Respawn,
+    deaths: u32,
+  ) -> Flyng {
+    Flyng {
+      ship: respawn.ship.clone(),
+      ticks: respawn.ticks,
+      simulated_millis: clock::ticks_to_millis(respawn.ticks),
+      resumed_from: respawn.ticks,
+      grab_queued: false,
+      pickups: respawn.pickups,
+      deaths,
+      splits: respawn.splits.clone(),
+      assisted: respawn.assisted,
+      respawn: Some(respawn),
+      trail: Trail::default(),
+      preview: Vec::new(),
+    }
+  }
+
+  fn snapshot(
+    &self,
+    level: &Level,
+  ) -> Respawn {
+    Respawn {
+      ship: self.ship.clone(),
+      ticks: self.ticks,
+      pickups: self.pickups,
+      collected: level.collected(),
+      splits: self.splits.clone(),
+      assisted: self.assisted,
+    }
+  }
+
+  /// Dots along the predicted path, fading out with distance.
+  fn draw_preview(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+  ) -> GameResult<()> {
+    if self.preview.len() < 2 {
+      return Ok(());
+    }
+
+    let mut mesh = graphics::MeshBuilder::new();
+    let count = self.preview.len() as f32;
+    for (i, point) in self.preview.iter().enumerate().step_by(4) {
+      let alpha = 0.8 * (1.0 - i as f32 / count);
+      mesh.circle(
+        graphics::DrawMode::fill(),
+        screen.point_game_to_screen((*point).into()),
+        screen.size_game_to_screen(3.0).max(1.0),
+        0.5,
+        [1.0, 1.0, 1.0, alpha].into()
+      )?;
+    }
+
+    let mesh = mesh.build(ctx)?;
+    graphics::draw(ctx, &mesh, graphics::DrawParam::default())
+  }
+
+  /// Where a manual restart goes: back to the last checkpoint, costing the
+  /// same as a death.
+  fn restart(&self) -> Rstrt {
+    Rstrt {deaths: self.deaths + 1, respawn: self.respawn.clone()}
+  }
+
+  fn score(&self) -> Score {
+    Score {
+      pickups: self.pickups,
+      millis: self.simulated_millis,
+      deaths: self.deaths,
+      assisted: self.assisted,
+    }
+  }
 }
 
-pub fn draw(
-  ctx: &mut Context,
-  screen: &ScreenContext,
-  level: &Level,
-  _millis_since_state_start: u64
-) -> GameResult<()> {
-  level.draw_level(ctx, screen)?;
+impl GameState for Flyng {
+  fn update(
+    &mut self,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    settings: &mut Settings,
+    input: &InputState,
+    millis_since_state_start: u64
+  ) -> Transition {
+    if input.was_pressed(Action::Pause) {
+      return Transition::Push(Box::new(Pause::new(self.restart())));
+    }
+    if input.was_pressed(Action::Restart) {
+      return Transition::Switch(Box::new(self.restart()));
+    }
+
+    self.grab_queued |= input.was_pressed(Action::Grab);
+    self.assisted |= settings.aim_assist;
+
+    // Physics runs in fixed ticks so a run plays out the same at any
+    // framerate; a grab waits for the next tick instead of being dropped.
+    let tick = tick_millis();
+    while self.ticks < self.resumed_from + clock::ticks_in(millis_since_state_start) {
+      self.ticks += 1;
+      self.simulated_millis = clock::ticks_to_millis(self.ticks);
+
+      level.animate(self.simulated_millis);
+      level.particles.update(tick);
+      for attractor in level.attractors.iter_mut() {
+        attractor.rotate(tick);
+      }
+
+      let before = self.ship.pos;
+      let events = self.ship.step(level, self.grab_queued, tick);
+      self.grab_queued = false;
 
-  Ok(())
-}
\ No newline at end of file
+      if events.captured.is_some() {
+        level.particles.emit(&Emitter::capture_ring(), self.ship.pos);
+      }
+      if let Some(i) = events.boosted {
+        let direction = level.zippers[i].segment().direction();
+        level.particles.emit(&Emitter::zipper_boost(direction), self.ship.pos);
+      }
+      if events.teleported.is_some() || events.wrapped {
+        self.trail.clear();
+      }
+      self.trail.push(self.ship.pos);
+
+      if self.ship.orbit.is_none() {
+        level.particles.emit(&Emitter::ship_trail(self.ship.vel), self.ship.pos);
+      }
+
+      if let Some(crash_pos) = events.crashed {
+        level.particles.emit(&Emitter::explosion(), crash_pos);
+        return Transition::Switch(Box::new(Dying::new(crash_pos.into(), self.deaths, self.respawn.take())));
+      }
+
+      // Teleports and wraps jump across the level rather than flying
+      // through it, so only the flight up to the portal or edge counts.
+      let motion = Segment::new(before, events.left_at.unwrap_or(self.ship.pos));
+      self.pickups += level.collect_along(&motion);
+
+      if level.pass_checkpoint_along(&motion).is_some() {
+        self.splits.push(self.simulated_millis);
+        self.respawn = Some(self.snapshot(level));
+      }
+
+      let all_passed = level.next_checkpoint().is_none();
+      if all_passed && level.goal.is_some_and(|goal| goal.reached_by(&motion)) {
+        return Transition::Switch(Box::new(Winng::new(self.score(), level.has_next())));
+      }
+    }
+
+    self.preview = if settings.aim_assist && self.ship.orbit.is_some() {
+      self.ship.predict_release(level, PREVIEW_TICKS, tick)
+    } else {
+      Vec::new()
+    };
+
+    screen.center_on(self.ship.pos.into());
+
+    Transition::None
+  }
+
+  fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    level: &Level,
+    _millis_since_state_start: u64
+  ) -> GameResult<()> {
+    level.draw_level(ctx, screen)?;
+    self.trail.draw(ctx, screen)?;
+    self.draw_preview(ctx, screen)?;
+    self.ship.draw(ctx, screen)?;
+    level.particles.draw(ctx, screen)?;
+
+    if let Some(&split) = self.splits.last() {
+      if self.simulated_millis < split + SPLIT_SHOW_MILLIS {
+        let layout = Layout::new(screen);
+        let text = layout.text(
+          TextFragment::new(format!("Checkpoint {}/{}  {:.2}s", self.splits.len(), level.checkpoints.len(), split as f32 / 1000.0))
+          .color([0.2, 0.9, 1.0, 1.0].into()),
+          32.0
+        );
+
+        layout.draw_text(ctx, &text, Anchor::Top, (0.0, 40.0))?;
+      }
+    }
+
+    Ok(())
+  }
+
+  fn in_run(&self) -> bool {
+    true
+  }
+
+  fn ship(&self) -> Option<&Ship> {
+    Some(&self.ship)
+  }
+
+  fn debug_lines(&self) -> Vec<String> {
+    vec![
+      format!("ticks: {}", self.ticks),
+      format!("simulated: {} ms", self.simulated_millis),
+      format!("pickups: {}  splits: {}", self.pickups, self.splits.len()),
+    ]
+  }
+}
@@ -1,22 +1,82 @@
 use super::super::screen_context::ScreenContext;
 use super::super::game_objects::level::Level;
-use super::State;
+use super::super::game_objects::ship::Ship;
+use super::super::settings::Settings;
+use super::super::input::{Action, InputState};
+use super::super::drawing_helpers::menu::{draw_dim, draw_options, move_cursor};
+use super::super::drawing_helpers::ui::{Anchor, Layout};
+use super::{GameState, Transition};
+use super::start::Start;
 
 use ggez::{GameResult, Context};
+use ggez::graphics::TextFragment;
 
-pub fn update(
-  screen: &mut ScreenContext,
-  level: &mut Level,
-  millis_since_state_start: u64
-) -> Option<State> {
-  None
+const OPTIONS: [&str; 2] = ["Play", "Quit"];
+
+/// Main menu, shown over the current level. Reached by quitting a run from
+/// the pause or win screens, or backing out of the win screen.
+#[derive(Default)]
+pub struct Menus {
+  pub cursor: usize,
 }
 
-pub fn draw(
-  ctx: &mut Context,
-  screen: &ScreenContext,
-  level: &Level,
-  millis_since_state_start: u64
-) -> GameResult<()> {
-  Ok(())
-}
\ No newline at end of file
+impl GameState for Menus {
+  /// Shows the level as a fresh attempt would find it, not as the last run
+  /// left it.
+  fn on_enter(
+    &mut self,
+    _screen: &mut ScreenContext,
+    level: &mut Level,
+  ) {
+    level.animate(0);
+    level.reset_collectibles();
+    level.reset_checkpoints();
+    level.particles.clear();
+  }
+
+  fn update(
+    &mut self,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    _settings: &mut Settings,
+    input: &InputState,
+    _millis_since_state_start: u64
+  ) -> Transition {
+    screen.center_on(Ship::spawn(level).pos.into());
+
+    self.cursor = move_cursor(
+      self.cursor,
+      OPTIONS.len(),
+      input.was_pressed(Action::Up),
+      input.was_pressed(Action::Down)
+    );
+
+    if input.was_pressed(Action::Confirm) {
+      match self.cursor {
+        0 => Transition::Switch(Box::new(Start::default())),
+        _ => Transition::Quit,
+      }
+    } else {
+      Transition::None
+    }
+  }
+
+  fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    level: &Level,
+    _millis_since_state_start: u64
+  ) -> GameResult<()> {
+    level.draw_level(ctx, screen)?;
+    draw_dim(ctx, screen, 0.6)?;
+
+    let layout = Layout::new(screen);
+    let title = layout.text(TextFragment::new("SkyShip").color([1.0, 1.0, 1.0, 1.0].into()), 72.0);
+    layout.draw_text(ctx, &title, Anchor::Top, (0.0, 120.0))?;
+
+    draw_options(ctx, screen, &OPTIONS, self.cursor)?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,107 @@
+use super::super::screen_context::ScreenContext;
+use super::super::game_objects::level::Level;
+use super::super::settings::Settings;
+use super::super::input::{Action, InputState};
+use super::super::drawing_helpers::menu::{draw_dim, draw_options, move_cursor};
+use super::{GameState, Transition};
+use super::rstrt::Rstrt;
+use super::menus::Menus;
+use super::setng::Setng;
+
+use ggez::{GameResult, Context};
+
+const OPTION_COUNT: usize = 4;
+
+pub struct Pause {
+  pub cursor: usize,
+  /// What "Restart" does: the same as the Restart key in the paused run, so
+  /// it keeps the run's deaths and checkpoint and counts as a death.
+  restart: Option<Rstrt>,
+}
+
+impl Pause {
+  pub fn new(restart: Rstrt) -> Pause {
+    Pause {
+      cursor: 0,
+      restart: Some(restart),
+    }
+  }
+
+  fn restart(&mut self) -> Rstrt {
+    self.restart.take().unwrap_or(Rstrt {deaths: 0, respawn: None})
+  }
+}
+
+impl GameState for Pause {
+  fn update(
+    &mut self,
+    _screen: &mut ScreenContext,
+    _level: &mut Level,
+    _settings: &mut Settings,
+    input: &InputState,
+    _millis_since_state_start: u64
+  ) -> Transition {
+    if input.was_pressed(Action::Pause) || input.was_pressed(Action::Back) {
+      return Transition::Pop;
+    }
+
+    self.cursor = move_cursor(
+      self.cursor,
+      OPTION_COUNT,
+      input.was_pressed(Action::Up),
+      input.was_pressed(Action::Down)
+    );
+
+    if input.was_pressed(Action::Confirm) {
+      match self.cursor {
+        0 => Transition::Pop,
+        1 => Transition::Clear(Box::new(self.restart())),
+        2 => Transition::Push(Box::new(Setng::default())),
+        _ => Transition::Clear(Box::new(Menus::default())),
+      }
+    } else {
+      Transition::None
+    }
+  }
+
+  fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    _level: &Level,
+    _millis_since_state_start: u64
+  ) -> GameResult<()> {
+    draw_dim(ctx, screen, 0.6)?;
+    draw_options(ctx, screen, &["Resume", "Restart", "Settings", "Quit to Menu"], self.cursor)?;
+
+    Ok(())
+  }
+
+  fn is_overlay(&self) -> bool {
+    true
+  }
+
+  fn in_run(&self) -> bool {
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use super::super::super::input::Binding;
+
+  use ggez::event::KeyCode;
+
+  #[test]
+  fn restart_goes_through_the_paused_runs_restart() {
+    let mut pause = Pause::new(Rstrt {deaths: 3, respawn: None});
+    let mut input = InputState::default();
+    input.press(Binding::Key(KeyCode::Down), Action::Down);
+    input.press(Binding::Key(KeyCode::Return), Action::Confirm);
+
+    let transition = pause.update(&mut ScreenContext::default(), &mut Level::default(), &mut Settings::default(), &input, 0);
+    assert!(matches!(transition, Transition::Clear(state) if state.name() == "Rstrt"));
+    assert!(pause.restart.is_none(), "the run's own restart, deaths and all, should be used");
+  }
+}
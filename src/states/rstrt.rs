@@ -1,22 +1,52 @@
 use super::super::screen_context::ScreenContext;
 use super::super::game_objects::level::Level;
-use super::State;
+use super::super::settings::Settings;
+use super::super::input::InputState;
+use super::super::clock;
+use super::{GameState, Transition};
+use super::start::Start;
+use super::flyng::{Flyng, Respawn};
 
 use ggez::{GameResult, Context};
 
-pub fn update(
-  screen: &mut ScreenContext,
-  level: &mut Level,
-  millis_since_state_start: u64
-) -> Option<State> {
-  None
+/// Sends the ship back to the last checkpoint it crossed, or to the level
+/// start if it hasn't crossed one.
+pub struct Rstrt {
+  pub deaths: u32,
+  pub respawn: Option<Respawn>,
 }
 
-pub fn draw(
-  ctx: &mut Context,
-  screen: &ScreenContext,
-  level: &Level,
-  millis_since_state_start: u64
-) -> GameResult<()> {
-  Ok(())
+impl GameState for Rstrt {
+  fn update(
+    &mut self,
+    _screen: &mut ScreenContext,
+    level: &mut Level,
+    _settings: &mut Settings,
+    _input: &InputState,
+    _millis_since_state_start: u64
+  ) -> Transition {
+    match self.respawn.take() {
+      Some(respawn) => {
+        level.animate(clock::ticks_to_millis(respawn.ticks));
+        level.restore(&respawn.collected, respawn.splits.len());
+        level.particles.reset(level.number, respawn.ticks);
+        Transition::Switch(Box::new(Flyng::resume(respawn, self.deaths)))
+      }
+      None => Transition::Switch(Box::new(Start {deaths: self.deaths})),
+    }
+  }
+
+  fn draw(
+    &self,
+    _ctx: &mut Context,
+    _screen: &ScreenContext,
+    _level: &Level,
+    _millis_since_state_start: u64
+  ) -> GameResult<()> {
+    Ok(())
+  }
+
+  fn in_run(&self) -> bool {
+    true
+  }
 }
\ No newline at end of file
@@ -0,0 +1,102 @@
+use super::super::screen_context::ScreenContext;
+use super::super::game_objects::level::Level;
+use super::super::settings::Settings;
+use super::super::input::{Action, InputState};
+use super::super::drawing_helpers::menu::{draw_dim, draw_options, move_cursor};
+use super::{GameState, Transition};
+use super::ctrls::Ctrls;
+
+use ggez::{GameResult, Context};
+
+const OPTION_COUNT: usize = 7;
+
+/// Settings menu, opened over the pause menu. Changes land in `Settings`
+/// straight away and are saved when it closes.
+#[derive(Default)]
+pub struct Setng {
+  pub cursor: usize,
+  /// Copy of the settings so `draw` can label the options.
+  shown: Settings,
+}
+
+impl Setng {
+  fn labels(&self) -> Vec<String> {
+    let on_off = |on: bool| if on {"On"} else {"Off"};
+    vec![
+      format!("Aim Assist: {}", on_off(self.shown.aim_assist)),
+      format!("Show Debug: {}", on_off(self.shown.show_debug)),
+      format!("Display: {}", self.shown.display_mode.name()),
+      format!("MSAA: {}x (restart)", self.shown.msaa),
+      format!("VSync: {} (restart)", on_off(self.shown.vsync)),
+      "Controls".to_string(),
+      "Back".to_string(),
+    ]
+  }
+}
+
+impl GameState for Setng {
+  fn update(
+    &mut self,
+    _screen: &mut ScreenContext,
+    _level: &mut Level,
+    settings: &mut Settings,
+    input: &InputState,
+    _millis_since_state_start: u64
+  ) -> Transition {
+    self.shown = settings.clone();
+
+    if input.was_pressed(Action::Pause) || input.was_pressed(Action::Back) {
+      return Transition::Pop;
+    }
+
+    self.cursor = move_cursor(
+      self.cursor,
+      OPTION_COUNT,
+      input.was_pressed(Action::Up),
+      input.was_pressed(Action::Down)
+    );
+
+    if !input.was_pressed(Action::Confirm) {
+      return Transition::None;
+    }
+
+    match self.cursor {
+      0 => settings.aim_assist = !settings.aim_assist,
+      1 => settings.show_debug = !settings.show_debug,
+      2 => settings.display_mode = settings.display_mode.next(),
+      3 => settings.cycle_msaa(),
+      4 => settings.vsync = !settings.vsync,
+      5 => return Transition::Push(Box::new(Ctrls::default())),
+      _ => return Transition::Pop,
+    }
+    self.shown = settings.clone();
+    Transition::None
+  }
+
+  fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    _level: &Level,
+    _millis_since_state_start: u64
+  ) -> GameResult<()> {
+    draw_dim(ctx, screen, 0.85)?;
+    let labels = self.labels();
+    let options: Vec<&str> = labels.iter().map(|label| label.as_str()).collect();
+    draw_options(ctx, screen, &options, self.cursor)?;
+
+    Ok(())
+  }
+
+  fn is_overlay(&self) -> bool {
+    true
+  }
+
+  fn in_run(&self) -> bool {
+    true
+  }
+
+  fn edits_settings(&self) -> bool {
+    true
+  }
+}
@@ -1,40 +1,77 @@
 use super::super::screen_context::ScreenContext;
 use super::super::game_objects::level::Level;
-use super::State;
+use super::super::game_objects::ship::Ship;
+use super::super::settings::Settings;
+use super::super::input::InputState;
+use super::super::drawing_helpers::ui::{Anchor, Layout};
+use super::{GameState, Transition};
+use super::flyng::Flyng;
 
 use ggez::{GameResult, Context};
-use ggez::graphics::{self, TextFragment, Text};
+use ggez::graphics::{self, TextFragment};
 
 use std::fs;
 
-pub fn update(
-  _screen: &mut ScreenContext,
-  _level: &mut Level,
-  millis_since_state_start: u64
-) -> Option<State> {
-  if millis_since_state_start >= 3000 {
-    Some(State::Flyng)
-  } else {
-    None
-  }
+/// Countdown before an attempt. `deaths` carries over from earlier attempts
+/// at the same run.
+#[derive(Default)]
+pub struct Start {
+  pub deaths: u32,
 }
 
-pub fn draw(
-  ctx: &mut Context,
-  screen: &ScreenContext,
-  level: &Level,
-  millis_since_state_start: u64
-) -> GameResult<()> {
-  level.draw_level(ctx, screen)?;
-
-  draw_number(
-    ctx,
-    screen,
-    3 - millis_since_state_start / 1000,
-    (millis_since_state_start as f32 / 1000.0) % 1.0
-  )?;
-  
-  Ok(())
+impl GameState for Start {
+  fn on_enter(
+    &mut self,
+    _screen: &mut ScreenContext,
+    level: &mut Level,
+  ) {
+    level.animate(0);
+    level.reset_collectibles();
+    level.reset_checkpoints();
+    level.particles.reset(level.number, 0);
+  }
+
+  fn update(
+    &mut self,
+    screen: &mut ScreenContext,
+    level: &mut Level,
+    _settings: &mut Settings,
+    _input: &InputState,
+    millis_since_state_start: u64
+  ) -> Transition {
+    let ship = Ship::spawn(level);
+    screen.center_on(ship.pos.into());
+
+    if millis_since_state_start >= 3000 {
+      Transition::Switch(Box::new(Flyng::new(ship, self.deaths)))
+    } else {
+      Transition::None
+    }
+  }
+
+  fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    level: &Level,
+    millis_since_state_start: u64
+  ) -> GameResult<()> {
+    level.draw_level(ctx, screen)?;
+    Ship::spawn(level).draw(ctx, screen)?;
+
+    draw_number(
+      ctx,
+      screen,
+      3 - millis_since_state_start / 1000,
+      (millis_since_state_start as f32 / 1000.0) % 1.0
+    )?;
+    
+    Ok(())
+  }
+
+  fn in_run(&self) -> bool {
+    true
+  }
 }
 
 fn draw_number(
@@ -45,6 +82,7 @@ fn draw_number(
 ) -> GameResult {
   let fade_in = (if cycle < 1.0 / 3.0 {cycle} else {1.0 / 3.0}) * 3.0;
 
+  let layout = Layout::new(screen);
   let size = (2.0 - fade_in) * 100.0;
 
   let colors: Vec<graphics::Color> = vec![
@@ -55,8 +93,7 @@ fn draw_number(
 
   let mut text_frag =
     TextFragment::new(number.to_string())
-    .color(colors[3 - number as usize])
-    .scale(graphics::PxScale::from(size));
+    .color(colors[3 - number as usize]);
 
   let mut font_path = std::env::current_exe().unwrap();
   font_path.pop();
@@ -68,11 +105,9 @@ fn draw_number(
 
   text_frag = text_frag.font(font);
   
-  let text = &Text::new(text_frag);
-  
-  let text_position = mint::Point2 {x: (screen.size.0 - text.width(ctx)) / 2.0, y: (screen.size.1 - text.height(ctx)) / 2.0};
+  let text = layout.text(text_frag, size);
 
-  graphics::draw(ctx, text, graphics::DrawParam::default().dest(text_position))?;
+  layout.draw_text(ctx, &text, Anchor::Center, (0.0, 0.0))?;
 
   Ok(())
 }
\ No newline at end of file
@@ -1,22 +1,138 @@
 use super::super::screen_context::ScreenContext;
 use super::super::game_objects::level::Level;
-use super::State;
+use super::super::settings::Settings;
+use super::super::input::{Action, InputState};
+use super::super::drawing_helpers::menu::{draw_dim, draw_options, move_cursor};
+use super::super::score::Score;
+use super::super::drawing_helpers::ui::{Anchor, Layout};
+use super::{GameState, Transition};
+use super::start::Start;
+use super::menus::Menus;
 
 use ggez::{GameResult, Context};
+use ggez::graphics::TextFragment;
 
-pub fn update(
-  screen: &mut ScreenContext,
-  level: &mut Level,
-  millis_since_state_start: u64
-) -> Option<State> {
-  None
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WinOption {
+  NextLevel,
+  PlayAgain,
+  QuitToMenu,
 }
 
-pub fn draw(
-  ctx: &mut Context,
-  screen: &ScreenContext,
-  level: &Level,
-  millis_since_state_start: u64
-) -> GameResult<()> {
-  Ok(())
-}
\ No newline at end of file
+impl WinOption {
+  fn label(self) -> &'static str {
+    match self {
+      WinOption::NextLevel => "Next Level",
+      WinOption::PlayAgain => "Play Again",
+      WinOption::QuitToMenu => "Quit to Menu",
+    }
+  }
+}
+
+const OPTIONS: [WinOption; 3] = [WinOption::NextLevel, WinOption::PlayAgain, WinOption::QuitToMenu];
+
+pub struct Winng {
+  pub score: Score,
+  pub cursor: usize,
+  /// Whether there is a level after this one; "Next Level" is left out on
+  /// the last.
+  has_next: bool,
+}
+
+impl Winng {
+  pub fn new(
+    score: Score,
+    has_next: bool,
+  ) -> Winng {
+    Winng {
+      score,
+      cursor: 0,
+      has_next,
+    }
+  }
+
+  fn options(&self) -> &'static [WinOption] {
+    if self.has_next {&OPTIONS} else {&OPTIONS[1..]}
+  }
+}
+
+impl GameState for Winng {
+  fn update(
+    &mut self,
+    _screen: &mut ScreenContext,
+    level: &mut Level,
+    _settings: &mut Settings,
+    input: &InputState,
+    _millis_since_state_start: u64
+  ) -> Transition {
+    if input.was_pressed(Action::Back) {
+      return Transition::Clear(Box::new(Menus::default()));
+    }
+
+    let options = self.options();
+    self.cursor = move_cursor(
+      self.cursor,
+      options.len(),
+      input.was_pressed(Action::Up),
+      input.was_pressed(Action::Down)
+    );
+
+    if input.was_pressed(Action::Confirm) {
+      match options[self.cursor] {
+        // The file was read when this level loaded, so this only fails if
+        // it changed since; the menu keeps the current level then.
+        WinOption::NextLevel => match level.load_next() {
+          Ok(()) => Transition::Clear(Box::new(Start::default())),
+          Err(_) => Transition::Clear(Box::new(Menus::default())),
+        },
+        WinOption::PlayAgain => Transition::Switch(Box::new(Start::default())),
+        WinOption::QuitToMenu => Transition::Clear(Box::new(Menus::default())),
+      }
+    } else {
+      Transition::None
+    }
+  }
+
+  fn draw(
+    &self,
+    ctx: &mut Context,
+    screen: &ScreenContext,
+    level: &Level,
+    _millis_since_state_start: u64
+  ) -> GameResult<()> {
+    level.draw_level(ctx, screen)?;
+    draw_dim(ctx, screen, 0.6)?;
+
+    let lines = [
+      ("Level Complete".to_string(), 48.0),
+      (format!("Pickups  {}", self.score.pickups), 28.0),
+      (format!("Time  {:.2}s  (+{})", self.score.millis as f32 / 1000.0, self.score.time_bonus()), 28.0),
+      (format!("Deaths  {}  (-{})", self.score.deaths, self.score.death_penalty()), 28.0),
+      (format!("Score  {}", self.score.total()), 40.0),
+    ];
+    let unranked = ("Aim assist used - unranked".to_string(), 22.0);
+    let lines: Vec<(String, f32)> = lines.iter().cloned().chain(self.score.assisted.then_some(unranked)).collect();
+
+    // Sits just above the options, which draw_options centres on screen.
+    // Offsets are in reference pixels from the top edge.
+    let layout = Layout::new(screen);
+    let block_height: f32 = lines.iter().map(|(_, size)| size * 1.3).sum();
+    let center = layout.size.1 / 2.0 / layout.scale;
+    let mut y = (center - self.options().len() as f32 * 20.0 - block_height - 20.0).max(0.0);
+    for (line, size) in lines.iter() {
+      let text = layout.text(TextFragment::new(line.as_str()).color([1.0, 1.0, 1.0, 1.0].into()), *size);
+      layout.draw_text(ctx, &text, Anchor::Top, (0.0, y))?;
+
+      y += size * 1.3;
+    }
+
+    let labels: Vec<&str> = self.options().iter().map(|option| option.label()).collect();
+    draw_options(ctx, screen, &labels, self.cursor)?;
+
+    Ok(())
+  }
+
+  fn level_complete(&self) -> bool {
+    true
+  }
+}